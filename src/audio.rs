@@ -1,33 +1,84 @@
-use std::{path::PathBuf, fs::File, num::NonZeroU32, io::Write};
+use std::{path::Path, fs::File, num::{NonZeroU32, NonZeroU8}, io::Write};
 use anyhow::{Result, anyhow};
 use id3::{Tag, TagLike};
 use mp3lame_encoder::{FlushNoGap, Id3Tag, InterleavedPcm, max_required_buffer_size, MonoPcm};
 use vorbis_rs::{VorbisDecoder, VorbisEncoderBuilder};
 use soundtouch::{SoundTouch, Setting};
 
-pub fn change_speed_wav(path: &PathBuf, rate: f64, change_pitch: bool) -> Result<(), hound::Error>{
+/// Amount of samples per channel passed to the vorbis encoder at once
+const OGG_BLOCK_SIZE: usize = 4096;
+
+/// The samples of an audio file, decoded once so they can be encoded at several rates
+pub enum DecodedAudio{
+    Wav{
+        spec: hound::WavSpec,
+        samples: Vec<f32>,
+    },
+    Ogg{
+        sampling_frequency: NonZeroU32,
+        channels: NonZeroU8,
+        /// Samples of each channel
+        samples: Vec<Vec<f32>>,
+    },
+    Mp3{
+        sample_rate: i32,
+        channels: usize,
+        bitrate: i32,
+        /// Interleaved samples of all channels
+        samples: Vec<i16>,
+        tag: Option<Tag>,
+    },
+}
+
+impl DecodedAudio{
+    /// Decodes an audio file based on its extension. Files with unknown extensions are decoded as mp3.
+    pub fn decode(path: &Path) -> Result<Self>{
+        match path.extension().unwrap_or(std::ffi::OsStr::new("")).to_str().unwrap(){
+            "ogg" => decode_ogg(path),
+            "wav" => Ok(decode_wav(path)?),
+            "mp3" => decode_mp3(path),
+            // Attempt to process file as mp3 if it is not a known file type
+            _ => decode_mp3(path).map_err(|_| anyhow!("Unsupported/unknown file type!"))
+        }
+    }
+
+    /// Writes the audio at the given rate to a file in the format it was decoded from.
+    pub fn encode(&self, out_path: &Path, rate: f64, change_pitch: bool) -> Result<()>{
+        match self{
+            DecodedAudio::Wav{ spec, samples } => Ok(encode_wav(out_path, *spec, samples, rate, change_pitch)?),
+            DecodedAudio::Ogg{ sampling_frequency, channels, samples } => encode_ogg(out_path, *sampling_frequency, *channels, samples, rate),
+            DecodedAudio::Mp3{ sample_rate, channels, bitrate, samples, tag } => encode_mp3(out_path, *sample_rate, *channels, *bitrate, samples, tag.as_ref(), rate, change_pitch),
+        }
+    }
+}
+
+fn decode_wav(path: &Path) -> Result<DecodedAudio, hound::Error>{
     let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let samples = reader.samples::<i16>().map(|x| x.unwrap() as f32).collect::<Vec<f32>>();
+    Ok(DecodedAudio::Wav{ spec, samples })
+}
+
+fn encode_wav(out_path: &Path, spec: hound::WavSpec, samples: &[f32], rate: f64, change_pitch: bool) -> Result<(), hound::Error>{
     let mut spec = hound::WavSpec{
-        ..reader.spec()
+        ..spec
     };
-
-    let samples = reader.samples::<i16>().map(|x| x.unwrap() as f32).collect::<Vec<f32>>();
     let out_data: Vec<f32>;
 
     if change_pitch{
         spec.sample_rate = (spec.sample_rate as f64 * rate) as u32;
-        out_data = samples;
+        out_data = samples.to_vec();
     }else{
         let mut soundtouch = SoundTouch::new();
         soundtouch
-            .set_sample_rate(reader.spec().sample_rate)
-            .set_channels(reader.spec().channels as u32)
+            .set_sample_rate(spec.sample_rate)
+            .set_channels(spec.channels as u32)
             .set_setting(Setting::UseQuickseek, 1)
             .set_tempo(rate);
-            out_data = soundtouch.generate_audio(&samples);
+            out_data = soundtouch.generate_audio(samples);
     }
 
-    let mut encoder = hound::WavWriter::create(format!("{}({})", path.display(), rate), spec)?;
+    let mut encoder = hound::WavWriter::create(out_path, spec)?;
 
     for sample in out_data{
         encoder.write_sample(sample)?;
@@ -36,59 +87,94 @@ pub fn change_speed_wav(path: &PathBuf, rate: f64, change_pitch: bool) -> Result
     Ok(())
 }
 
-pub fn change_speed_ogg(path: &PathBuf, rate: f64, change_pitch: bool) -> Result<()>{
+fn decode_ogg(path: &Path) -> Result<DecodedAudio>{
     let mut source_ogg = File::open(path)?;
-    let mut transcoded_ogg = Vec::new();
     let mut decoder = VorbisDecoder::new(&mut source_ogg)?;
+    let mut samples = vec![Vec::new(); decoder.channels().get() as usize];
+    while let Some(decoded_block) = decoder.decode_audio_block()? {
+        for (channel, block) in samples.iter_mut().zip(decoded_block.samples()){
+            channel.extend_from_slice(block);
+        }
+    }
+    Ok(DecodedAudio::Ogg{
+        sampling_frequency: decoder.sampling_frequency(),
+        channels: decoder.channels(),
+        samples,
+    })
+}
+
+fn encode_ogg(out_path: &Path, sampling_frequency: NonZeroU32, channels: NonZeroU8, samples: &[Vec<f32>], rate: f64) -> Result<()>{
+    let mut transcoded_ogg = Vec::new();
     let mut encoder = VorbisEncoderBuilder::new(
-        NonZeroU32::new((decoder.sampling_frequency().get() as f64 * rate) as u32).unwrap(),
-        decoder.channels(),
+        NonZeroU32::new((sampling_frequency.get() as f64 * rate) as u32).unwrap(),
+        channels,
         &mut transcoded_ogg
     )?.build()?;
 
     // TODO: add functionality to not modify pitch
 
-    while let Some(decoded_block) = decoder.decode_audio_block()? {
-        encoder.encode_audio_block(decoded_block.samples())?;
+    let len = samples.first().map_or(0, Vec::len);
+    for start in (0..len).step_by(OGG_BLOCK_SIZE){
+        let end = (start + OGG_BLOCK_SIZE).min(len);
+        let block = samples.iter().map(|x| &x[start..end]).collect::<Vec<&[f32]>>();
+        encoder.encode_audio_block(&block)?;
     }
 
     encoder.finish()?;
-    File::create(format!("{}({}).ogg", path.parent().unwrap().join(path.file_stem().unwrap()).display(), rate))?.write_all(transcoded_ogg.as_slice())?;
+    File::create(out_path)?.write_all(transcoded_ogg.as_slice())?;
     Ok(())
 }
 
-pub fn change_speed_mp3(path: &PathBuf, rate: f64, change_pitch: bool) -> Result<()>{
-    let mut mp3_data: Vec<u8> = Vec::new();
+fn decode_mp3(path: &Path) -> Result<DecodedAudio>{
     let mut decoder = minimp3::Decoder::new(File::open(path)?);
-    let tag = Tag::read_from_path(path);
+    let tag = Tag::read_from_path(path).ok();
     let mp3_headers = decoder.next_frame()?;
+
+    let len = mp3_headers.data.len();
+    let mut samples: Vec<i16> = mp3_headers.data;
+    samples.reserve(len * 3000);
+    while let Ok(mut frame) = decoder.next_frame() {
+        samples.append(&mut frame.data);
+    }
+    Ok(DecodedAudio::Mp3{
+        sample_rate: mp3_headers.sample_rate,
+        channels: mp3_headers.channels,
+        bitrate: mp3_headers.bitrate,
+        samples,
+        tag,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn encode_mp3(out_path: &Path, sample_rate: i32, channels: usize, bitrate: i32, samples: &[i16], tag: Option<&Tag>, rate: f64, change_pitch: bool) -> Result<()>{
+    let mut mp3_data: Vec<u8> = Vec::new();
     let mut encoder = mp3lame_encoder::Builder::new().ok_or(anyhow!("Could not instantiate an mp3 builder"))?;
 
-    encoder.set_num_channels(mp3_headers.channels as u8).map_err(|e| anyhow!("Could not set mp3 encoder channels: {}", e))?;
+    encoder.set_num_channels(channels as u8).map_err(|e| anyhow!("Could not set mp3 encoder channels: {}", e))?;
     encoder.set_quality(mp3lame_encoder::Quality::Best).map_err(|e| anyhow!("Could not set mp3 encoder quality: {}", e))?;
     encoder.set_mode(mp3lame_encoder::Mode::Stereo).map_err(|e| anyhow!("Could not set mp3 audio mode: {}", e))?;
 
-    encoder.set_brate(match mp3_headers.bitrate {
-        _ if mp3_headers.bitrate >= 320 => mp3lame_encoder::Bitrate::Kbps320,
-        _ if mp3_headers.bitrate >= 256 => mp3lame_encoder::Bitrate::Kbps256,
-        _ if mp3_headers.bitrate >= 224 => mp3lame_encoder::Bitrate::Kbps224,
-        _ if mp3_headers.bitrate >= 192 => mp3lame_encoder::Bitrate::Kbps192,
-        _ if mp3_headers.bitrate >= 160 => mp3lame_encoder::Bitrate::Kbps160,
-        _ if mp3_headers.bitrate >= 128 => mp3lame_encoder::Bitrate::Kbps128,
-        _ if mp3_headers.bitrate >= 112 => mp3lame_encoder::Bitrate::Kbps112,
-        _ if mp3_headers.bitrate >= 96  => mp3lame_encoder::Bitrate::Kbps96,
-        _ if mp3_headers.bitrate >= 80  => mp3lame_encoder::Bitrate::Kbps80,
-        _ if mp3_headers.bitrate >= 64  => mp3lame_encoder::Bitrate::Kbps64,
-        _ if mp3_headers.bitrate >= 48  => mp3lame_encoder::Bitrate::Kbps48,
-        _ if mp3_headers.bitrate >= 40  => mp3lame_encoder::Bitrate::Kbps40,
-        _ if mp3_headers.bitrate >= 32  => mp3lame_encoder::Bitrate::Kbps32,
-        _ if mp3_headers.bitrate >= 24  => mp3lame_encoder::Bitrate::Kbps24,
-        _ if mp3_headers.bitrate >= 16  => mp3lame_encoder::Bitrate::Kbps16,
-        _ if mp3_headers.bitrate >= 8   => mp3lame_encoder::Bitrate::Kbps8,
+    encoder.set_brate(match bitrate {
+        _ if bitrate >= 320 => mp3lame_encoder::Bitrate::Kbps320,
+        _ if bitrate >= 256 => mp3lame_encoder::Bitrate::Kbps256,
+        _ if bitrate >= 224 => mp3lame_encoder::Bitrate::Kbps224,
+        _ if bitrate >= 192 => mp3lame_encoder::Bitrate::Kbps192,
+        _ if bitrate >= 160 => mp3lame_encoder::Bitrate::Kbps160,
+        _ if bitrate >= 128 => mp3lame_encoder::Bitrate::Kbps128,
+        _ if bitrate >= 112 => mp3lame_encoder::Bitrate::Kbps112,
+        _ if bitrate >= 96  => mp3lame_encoder::Bitrate::Kbps96,
+        _ if bitrate >= 80  => mp3lame_encoder::Bitrate::Kbps80,
+        _ if bitrate >= 64  => mp3lame_encoder::Bitrate::Kbps64,
+        _ if bitrate >= 48  => mp3lame_encoder::Bitrate::Kbps48,
+        _ if bitrate >= 40  => mp3lame_encoder::Bitrate::Kbps40,
+        _ if bitrate >= 32  => mp3lame_encoder::Bitrate::Kbps32,
+        _ if bitrate >= 24  => mp3lame_encoder::Bitrate::Kbps24,
+        _ if bitrate >= 16  => mp3lame_encoder::Bitrate::Kbps16,
+        _ if bitrate >= 8   => mp3lame_encoder::Bitrate::Kbps8,
         _ => mp3lame_encoder::Bitrate::Kbps96,
     }).map_err(|e| anyhow!("Could not set mp3 bitrate: {}", e))?;
 
-    if let Ok(tag) = &tag{
+    if let Some(tag) = tag{
         let year: Box<[u8]> = if let Some(year) = tag.year(){
             year.to_string().as_bytes().into()
         }else{
@@ -102,33 +188,28 @@ pub fn change_speed_mp3(path: &PathBuf, rate: f64, change_pitch: bool) -> Result
             year: year.as_ref()
         });
     }
-    
-    let len = mp3_headers.data.len();
-    let mut input: Vec<i16> = mp3_headers.data;
-    input.reserve(len * 3000);
-    while let Ok(mut frame) = decoder.next_frame() {
-        input.append(&mut frame.data);
-    }
 
-    encoder.set_sample_rate(
-        if change_pitch{
-            (mp3_headers.sample_rate as f64 * rate) as u32
-        }else{
-            let mut soundtouch = SoundTouch::new();
-            soundtouch
-                .set_sample_rate(mp3_headers.sample_rate as u32)
-                .set_channels(mp3_headers.channels as u32)
-                .set_setting(Setting::UseQuickseek, 1)
-                .set_tempo(rate);
-                input = soundtouch.generate_audio(input.into_iter().map(|x| x as f32).collect::<Vec<f32>>().as_slice())
-                    .into_iter().map(|x| x as i16).collect::<Vec<i16>>();
-            mp3_headers.sample_rate as u32
-        }
-    ).map_err(|e| anyhow!("Could not set mp3 sample rate: {}", e))?;
+    // The decoded samples are shared between rates, so only samples changed by soundtouch are owned
+    let stretched: Vec<i16>;
+    let input: &[i16] = if change_pitch{
+        encoder.set_sample_rate((sample_rate as f64 * rate) as u32).map_err(|e| anyhow!("Could not set mp3 sample rate: {}", e))?;
+        samples
+    }else{
+        let mut soundtouch = SoundTouch::new();
+        soundtouch
+            .set_sample_rate(sample_rate as u32)
+            .set_channels(channels as u32)
+            .set_setting(Setting::UseQuickseek, 1)
+            .set_tempo(rate);
+        stretched = soundtouch.generate_audio(samples.iter().map(|x| *x as f32).collect::<Vec<f32>>().as_slice())
+            .into_iter().map(|x| x as i16).collect::<Vec<i16>>();
+        encoder.set_sample_rate(sample_rate as u32).map_err(|e| anyhow!("Could not set mp3 sample rate: {}", e))?;
+        &stretched
+    };
 
     let mut encoder = encoder.build().map_err(|e| anyhow!("Could not build mp3 encoder: {}", e))?;
     if encoder.num_channels() == 1 {
-        let input = MonoPcm(&input);
+        let input = MonoPcm(input);
         mp3_data.reserve(max_required_buffer_size(input.0.len()));
         let encoded_size = encoder.encode(input, mp3_data.spare_capacity_mut()).map_err(|e| anyhow!(e))?;
         unsafe {
@@ -139,23 +220,22 @@ pub fn change_speed_mp3(path: &PathBuf, rate: f64, change_pitch: bool) -> Result
             mp3_data.set_len(mp3_data.len().wrapping_add(encoded_size));
         }
     }else{
-        let input = InterleavedPcm(&input);
+        let input = InterleavedPcm(input);
         mp3_data.reserve(max_required_buffer_size(input.0.len()));
         let encoded_size = encoder.encode(input, mp3_data.spare_capacity_mut()).map_err(|e| anyhow!(e))?;
         unsafe {
             mp3_data.set_len(mp3_data.len().wrapping_add(encoded_size));
         }
         let encoded_size = encoder.flush::<FlushNoGap>(mp3_data.spare_capacity_mut()).map_err(|e| anyhow!(e))?;
-        
+
         unsafe {
             mp3_data.set_len(mp3_data.len().wrapping_add(encoded_size));
         }
-    } 
+    }
 
-    let out_path = PathBuf::from(format!("{}({}).mp3", path.parent().unwrap().join(path.file_stem().unwrap()).display(), rate));
-    let mut file = File::create(&out_path)?;
+    let mut file = File::create(out_path)?;
     file.write_all(mp3_data.as_slice())?;
-    if let Ok(tag) = tag {
+    if let Some(tag) = tag {
         tag.write_to_path(out_path, id3::Version::Id3v24)?;
     }
 
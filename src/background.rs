@@ -0,0 +1,48 @@
+use std::path::Path;
+use anyhow::{Result, anyhow};
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage, imageops::FilterType};
+use crate::structs::{BackgroundOptions, BackgroundReplacement};
+
+/// Size of the backgrounds generated for solid colours. osu! stretches backgrounds to fit the
+/// screen, so a small image is enough.
+const SOLID_WIDTH: u32 = 640;
+const SOLID_HEIGHT: u32 = 360;
+
+/// Processes the background of a map with the given options and writes it to `out_path` as a jpg.
+/// `source` is the path of the original background, if the map has one.
+pub fn process_background(source: Option<&Path>, options: &BackgroundOptions, out_path: &Path) -> Result<()>{
+    let mut image = match &options.replacement{
+        Some(BackgroundReplacement::Colour(colour)) => {
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(SOLID_WIDTH, SOLID_HEIGHT, Rgb([colour.0, colour.1, colour.2])))
+        },
+        Some(BackgroundReplacement::Image(path)) => image::open(path)
+            .map_err(|e| anyhow!("Could not open background image '{}': {}", path.display(), e))?,
+        None => {
+            let source = source.ok_or(anyhow!("The map does not have a background to process."))?;
+            image::open(source).map_err(|e| anyhow!("Could not open background image '{}': {}", source.display(), e))?
+        }
+    };
+
+    // Downscale before blurring since blurring large images is slow
+    if let Some(max_height) = options.max_height{
+        if image.height() > max_height{
+            let width = (image.width() as u64 * max_height as u64 / image.height() as u64) as u32;
+            image = image.resize_exact(width.max(1), max_height, FilterType::Lanczos3);
+        }
+    }
+    if options.blur > 0.0{
+        image = image.blur(options.blur);
+    }
+
+    let mut image = image.into_rgb8();
+    if options.dim > 0{
+        let brightness = 1.0 - options.dim.min(100) as f32 / 100.0;
+        for pixel in image.pixels_mut(){
+            for channel in pixel.0.iter_mut(){
+                *channel = (*channel as f32 * brightness).round() as u8;
+            }
+        }
+    }
+    image.save_with_format(out_path, ImageFormat::Jpeg)?;
+    Ok(())
+}
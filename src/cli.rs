@@ -2,7 +2,8 @@ use std::{process::{exit, Command, Child}, path::PathBuf, io::{stdout, IsTermina
 
 use anyhow::{Result, anyhow};
 use futures_util::StreamExt;
-use crate::{structs::{MapOptions, Settings}, utils::{generate_map, gosu_startup, round_dec, clean_maps, calculate_space}};
+use libosu::data::Mode;
+use crate::{compare::MapStats, export::map_json, fidelity::verify_map, ladder::Ladder, timing::{DifficultyTarget, TimedValue}, structs::{BackgroundReplacement, BpmTarget, LongNotes, MapOptions, Mirror, Mods, Performance, Settings, Severity, Spinners}, utils::{generate_ladder, generate_map, gosu_startup, round_dec, clean_maps, calculate_space, new_seed}};
 use serde_json::Value;
 use tokio_tungstenite::connect_async;
 
@@ -15,33 +16,69 @@ pub async fn run() -> Result<()>{
     // Create my own arg parser because clap is bloated
     let args = Vec::from_iter(std::env::args());
     let mut args = args.iter().skip(1).map(AsRef::as_ref).collect::<Vec<&str>>();
-    const AVAILABLE_COMMANDS: [&str; 34] = [
+
+    // Commands take different arguments than the options, so they are handled first
+    match args.first(){
+        Some(&"verify") => return verify(&args[1..]),
+        Some(&"compare") => return compare(&args[1..]),
+        _ => ()
+    }
+    const AVAILABLE_COMMANDS: [&str; 86] = [
         "-a", "--approach-rate",
         "-A", "+A",
         "-b", "--bpm",
+        "-B", "--blur",
         "-c", "--circle-size",
         "-C", "--clean",
         "-d", "--hp-drain",
+        "-D", "--dim",
+        "-e", "--no-storyboard",
         "-f", "+f",
+        "-F", "+F",
         "-h", "--help",
         "-g", "--gosumemory",
+        "-G", "--ln-gap",
+        "-H", "--downscale",
+        "-i", "--accuracy",
+        "-I", "--background",
+        "-j", "--json",
+        "-k", "--tick-rate",
+        "-K", "--stack-leniency",
+        "-l", "--sliders",
+        "-L", "--long-notes",
+        "-m", "--mods",
+        "-M", "--mirror",
+        "-n", "--spinners",
+        "-N", "--misses",
         "-o", "--overall-difficulty",
         "-O", "+O",
         "-p", "--path",
         "-P", "+P",
+        "-q", "--bpm-target",
         "-r", "--rate",
+        "-R", "--rotate",
+        "-x", "--random",
+        "-y", "--stars",
+        "-s", "--spacing",
+        "-S", "--slider-velocity",
+        "-t", "--thin-streams",
+        "-T", "--convert",
         "-u", "--used-space",
         "-V", "--version",
+        "-w", "--ladder",
         "-z", "+z"
     ];
 
-    const FLAGS: [&str; 20] = [
+    const FLAGS: [&str; 26] = [
         "-h", "--help",
         "-V", "--version",
         "-A", "+A",
         "-C", "--clean",
+        "-e", "--no-storyboard",
         "-f", "+f",
+        "-F", "+F",
         "-g", "--gosumemory",
+        "-j", "--json",
         "-O", "+O",
         "-P", "+P",
         "-u", "--used-space",
@@ -87,8 +124,18 @@ pub async fn run() -> Result<()>{
     settings.cs_lock = false;
     settings.hp_lock = false;
     settings.od_lock = false;
+    settings.sm_lock = false;
+    settings.tr_lock = false;
+    settings.sl_lock = false;
 
     let mut bpm: Option<usize> = None;
+    let mut bpm_target = BpmTarget::Dominant;
+    let mut target_stars: Option<f64> = None;
+    let mut ar_target: Option<DifficultyTarget> = None;
+    let mut od_target: Option<DifficultyTarget> = None;
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut json = false;
+    let mut ladder: Option<Ladder> = None;
 
     // Iterate over each argument and apply the respective changes to the map
     // Stepping by 2 since args are in the format: [command, value]
@@ -96,7 +143,10 @@ pub async fn run() -> Result<()>{
     for ind in (0..args.len()).step_by(2){
         match args[ind]{
             "-a"| "--approach-rate" => {
-                map.approach_rate = args[ind+1].parse::<f64>()?;
+                match args[ind+1].parse::<DifficultyTarget>()?{
+                    DifficultyTarget::Value(ar) => map.approach_rate = ar,
+                    target => ar_target = Some(target)
+                }
                 settings.ar_lock = true;
             },
             "-A" => settings.scale_ar = false,
@@ -105,6 +155,10 @@ pub async fn run() -> Result<()>{
                 Ok(k) => k,
                 Err(e) => return Err(anyhow!("Could not parse bpm: {}\nNote that bpm values must be positive integers.", e))
             }),
+            "-B"| "--blur" => map.background_options.blur = match args[ind+1].parse::<f32>(){
+                Ok(k) if k >= 0.0 => k,
+                _ => return Err(anyhow!("Invalid blur: '{}'. The blur must be a positive number.", args[ind+1]))
+            },
             "-c"| "--circle-size" => {
                 map.circle_size = args[ind+1].parse::<f64>()?;
                 settings.cs_lock = true;
@@ -122,8 +176,15 @@ pub async fn run() -> Result<()>{
                 map.hp_drain = args[ind+1].parse::<f64>()?;
                 settings.hp_lock = true;
             },
+            "-D"| "--dim" => map.background_options.dim = match args[ind+1].trim_end_matches('%').parse::<u8>(){
+                Ok(k) if k <= 100 => k,
+                _ => return Err(anyhow!("Invalid dim: '{}'. The dim must be a percentage between 0 and 100.", args[ind+1]))
+            },
+            "-e"| "--no-storyboard" => map.strip_storyboard = true,
             "-f" => settings.force_generation = false,
             "+f" => settings.force_generation = true,
+            "-F" => settings.allow_lossy = false,
+            "+F" => settings.allow_lossy = true,
             "-h"| "--help" => {
                 print_help();
                 exit(0);
@@ -146,8 +207,53 @@ pub async fn run() -> Result<()>{
                 },
                 Err(e) => return Err(anyhow!("Could not start gosumemory: {}", e))
             },
+            "-G"| "--ln-gap" => map.ln_gap = match args[ind+1].trim_start_matches("1/").parse::<u32>(){
+                Ok(k) if k > 0 => k,
+                _ => return Err(anyhow!("Invalid beat divisor: '{}'. Use a divisor such as '4' or '1/4'.", args[ind+1]))
+            },
+            "-H"| "--downscale" => map.background_options.max_height = match args[ind+1].trim_end_matches('p').parse::<u32>(){
+                Ok(k) if k > 0 => Some(k),
+                _ => return Err(anyhow!("Invalid height: '{}'. Use a height in pixels such as '1080'.", args[ind+1]))
+            },
+            "-i"| "--accuracy" => settings.pp_accuracy = match args[ind+1].trim_end_matches('%').parse::<f64>(){
+                Ok(k) if (0.0..=100.0).contains(&k) => k,
+                _ => return Err(anyhow!("Invalid accuracy: '{}'. The accuracy must be a percentage between 0 and 100.", args[ind+1]))
+            },
+            "-I"| "--background" => map.background_options.replacement = Some(args[ind+1].parse::<BackgroundReplacement>()?),
+            "-j"| "--json" => json = true,
+            "-k"| "--tick-rate" => {
+                map.slider_tick_rate = args[ind+1].parse::<f64>()?;
+                settings.tr_lock = true;
+            },
+            "-K"| "--stack-leniency" => {
+                map.stack_leniency = args[ind+1].parse::<f64>()?;
+                settings.sl_lock = true;
+            },
+            "-l"| "--sliders" => match args[ind+1]{
+                "keep" => map.sliders_to_circles = false,
+                "circles" => {
+                    map.sliders_to_circles = true;
+                    map.slider_tail_circles = false;
+                },
+                "tails" => {
+                    map.sliders_to_circles = true;
+                    map.slider_tail_circles = true;
+                },
+                x => return Err(anyhow!("Invalid slider option: '{}'. Valid options are 'keep', 'circles' and 'tails'.", x))
+            },
+            "-L"| "--long-notes" => map.long_notes = args[ind+1].parse::<LongNotes>()?,
+            "-m"| "--mods" => map.mods = args[ind+1].parse::<Mods>()?,
+            "-M"| "--mirror" => map.mirror = args[ind+1].parse::<Mirror>()?,
+            "-n"| "--spinners" => map.spinners = args[ind+1].parse::<Spinners>()?,
+            "-N"| "--misses" => settings.pp_misses = match args[ind+1].parse::<u32>(){
+                Ok(k) => k,
+                Err(e) => return Err(anyhow!("Could not parse misses: {}\nNote that the amount of misses must be a positive integer.", e))
+            },
             "-o"| "--overall-difficulty" => {
-                map.overall_difficulty = args[ind+1].parse::<f64>()?;
+                match args[ind+1].parse::<DifficultyTarget>()?{
+                    DifficultyTarget::Value(od) => map.overall_difficulty = od,
+                    target => od_target = Some(target)
+                }
                 settings.od_lock = true;
             },
             "-O" => settings.scale_od = false,
@@ -155,16 +261,49 @@ pub async fn run() -> Result<()>{
             "-p"| "--path" => {
                 let temp_path: PathBuf = args[ind+1].into();
                 if temp_path.exists(){
-                    map.map_path = temp_path;
+                    paths.push(temp_path);
                 }else if settings.songs_path.join(&temp_path).exists(){
-                    map.map_path = settings.songs_path.join(temp_path);
+                    paths.push(settings.songs_path.join(temp_path));
                 }else{
                     return Err(anyhow!("The provided path: '{}' does not exist", temp_path.display()));
                 }
             },
             "-P" => settings.change_pitch = false,
             "+P" => settings.change_pitch = true,
+            "-q"| "--bpm-target" => bpm_target = args[ind+1].parse::<BpmTarget>()?,
             "-r"| "--rate" => map.rate = args[ind+1].parse::<f64>()?,
+            "-R"| "--rotate" => map.rotation = args[ind+1].parse::<f64>()?,
+            "-x"| "--random" => map.random_seed = Some(match args[ind+1]{
+                "new" => new_seed(),
+                seed => match seed.parse::<u64>(){
+                    Ok(k) => k,
+                    Err(e) => return Err(anyhow!("Could not parse seed: {}\nNote that seeds must be positive integers or 'new'.", e))
+                }
+            }),
+            "-y"| "--stars" => target_stars = Some(match args[ind+1].parse::<f64>(){
+                Ok(k) if k > 0.0 => k,
+                _ => return Err(anyhow!("Invalid star rating: '{}'. The star rating must be a positive number.", args[ind+1]))
+            }),
+            "-s"| "--spacing" => map.spacing = match args[ind+1].parse::<f64>(){
+                Ok(k) if k >= 0.0 => k,
+                Ok(_) => return Err(anyhow!("The spacing factor can not be negative.")),
+                Err(e) => return Err(anyhow!("Could not parse spacing: {}", e))
+            },
+            "-S"| "--slider-velocity" => {
+                map.slider_multiplier = args[ind+1].parse::<f64>()?;
+                settings.sm_lock = true;
+            },
+            "-t"| "--thin-streams" => map.stream_thinning = match args[ind+1].trim_start_matches("1/").parse::<u32>(){
+                Ok(k) if k > 0 => Some(k),
+                _ => return Err(anyhow!("Invalid beat divisor: '{}'. Use a divisor such as '4' or '1/4'.", args[ind+1]))
+            },
+            "-T"| "--convert" => map.convert_mode = Some(match args[ind+1].to_lowercase().as_str(){
+                "osu" | "standard" => Mode::Osu,
+                "taiko" => Mode::Taiko,
+                "catch" | "fruits" => Mode::Catch,
+                "mania" => Mode::Mania,
+                x => return Err(anyhow!("Invalid mode: '{}'. Valid modes are 'osu', 'taiko', 'catch' and 'mania'.", x))
+            }),
             "-u"| "--used-space" => {
                 println!("{}", calculate_space("maps.txt")?);
                 exit(0);
@@ -173,6 +312,7 @@ pub async fn run() -> Result<()>{
                 println!("Ruso v{}", env!("CARGO_PKG_VERSION"));
                 exit(0);
             }
+            "-w"| "--ladder" => ladder = Some(args[ind+1].parse::<Ladder>()?),
             "-z" => settings.generate_osz = false,
             "+z" => settings.generate_osz = true,
             _ => return Err(anyhow!("Invalid command: {}\n Run 'ruso --help' for help information.", args[ind]))
@@ -180,13 +320,14 @@ pub async fn run() -> Result<()>{
     }
 
     // Attempt to get the path from the gosu websocket url if no path was provided
-    if map.map_path == PathBuf::new(){
+    if paths.is_empty(){
         writeln!(stderr(), "No path specified, attempting to get path from gosu!")?;
-        map.map_path = match path_from_gosu(&settings).await{
+        let path = match path_from_gosu(&settings).await{
             Ok(path) => path,
             Err(e) => return Err(anyhow!("Could not connect to gosu: {}", e))
         };
-        writeln!(stderr(), "Got path from gosu: {}", map.map_path.display())?;
+        writeln!(stderr(), "Got path from gosu: {}", path.display())?;
+        paths.push(path);
     }
 
     // Kill gosumemory if it was started by ruso
@@ -201,18 +342,59 @@ pub async fn run() -> Result<()>{
         }
     }
 
-    // Get metadata for the map and set its rate based on
-    // bpm if it was provided
-    map.read_map_metadata(&settings)?;
-    if let Some(bpm) = bpm{
-        map.rate = round_dec(bpm as f64/map.bpm as f64, 2);
+    // The paths are already resolved, so the songs path is cleared to make reading and generating the maps
+    // use them as they are. This avoids conflicts with paths in cwd and paths that start with the osu! songs path.
+    settings.songs_path = PathBuf::new();
+    let targets = MapTargets{
+        bpm,
+        bpm_target,
+        stars: target_stars,
+        approach_rate: ar_target,
+        overall_difficulty: od_target,
+    };
+
+    // Only the JSON is printed to stdout so it can be piped into other programs
+    if json{
+        let mut maps = Vec::new();
+        for path in paths{
+            let mut map = map.clone();
+            map.map_path = path;
+            prepare_map(&mut map, &settings, &targets)?;
+            // Every step of a ladder is exported like a map of its own
+            let steps = match &ladder{
+                Some(ladder) => ladder.steps(&map, &settings)?,
+                None => vec![map]
+            };
+            for step in steps{
+                maps.push(map_json(&step, &settings).map_err(|e| anyhow!("Could not read '{}': {}", step.map_path.display(), e))?);
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&maps)?);
+        return Ok(());
     }
 
-    // Making the generate_map function generate the path only from map in order to avoid conflicts
-    // with paths in cwd and paths that start with the provided osu! songs path.
-    settings.songs_path = PathBuf::new();
-    writeln!(stderr(), "Generating map...")?;
-    generate_map(&map, &settings).await?;
+    let multiple_maps = paths.len() > 1;
+    for path in paths{
+        let mut map = map.clone();
+        map.map_path = path;
+        if multiple_maps{
+            println!("{}:", map.map_path.display());
+        }
+        prepare_map(&mut map, &settings, &targets)?;
+        if let Some(ladder) = &ladder{
+            let steps = ladder.steps(&map, &settings)?;
+            for step in &steps{
+                let stars = step.performance(&settings).map(|x| x.stars.to_string()).unwrap_or_else(|_| "?".to_string());
+                println!("{}: {}x AR {} OD {} ({} stars)", step.ladder_name().unwrap_or_default(), step.rate, step.approach_rate, step.overall_difficulty, stars);
+            }
+            writeln!(stderr(), "Generating {} maps...", steps.len())?;
+            generate_ladder(&steps, &settings).await?;
+            continue;
+        }
+        print_map_info(&map, &settings)?;
+        writeln!(stderr(), "Generating map...")?;
+        generate_map(&map, &settings).await?;
+    }
 
     // Fix terminal carriage return
     if let Ok(mut process) = Command::new("stty").arg("sane").spawn(){
@@ -223,6 +405,134 @@ pub async fn run() -> Result<()>{
     Ok(())
 }
 
+/// Values given on the command line that depend on the metadata of each map
+struct MapTargets{
+    bpm: Option<usize>,
+    bpm_target: BpmTarget,
+    stars: Option<f64>,
+    approach_rate: Option<DifficultyTarget>,
+    overall_difficulty: Option<DifficultyTarget>,
+}
+
+/// Reads the metadata of the map and applies the targets to it.
+fn prepare_map(map: &mut MapOptions, settings: &Settings, targets: &MapTargets) -> Result<()>{
+    // Get metadata for the map and set its rate based on
+    // bpm if it was provided
+    map.read_map_metadata(settings)?;
+    if let Some(bpm) = targets.bpm{
+        map.rate = round_dec(bpm as f64/(map.bpm_info.target(targets.bpm_target) * map.mods.clock_rate()), 2);
+        // Apply the mods again since AR and OD scaling depend on the new rate
+        map.apply_mods(settings);
+    }
+    if let Some(target) = targets.stars{
+        let stars = map.solve_rate_for_stars(settings, target)
+            .map_err(|e| anyhow!("Could not reach {} stars: {}", target, e))?;
+        writeln!(stderr(), "Reached {} stars at {}x", stars, map.rate)?;
+    }
+    // Effective values depend on the final rate, so they are converted last
+    let timed_values = [
+        (TimedValue::ApproachRate, targets.approach_rate),
+        (TimedValue::OverallDifficulty(map.target_mode()), targets.overall_difficulty),
+    ];
+    for (value, target) in timed_values{
+        if let Some(warning) = target.and_then(|x| map.set_timed_value(value, x)){
+            writeln!(stderr(), "Warning: {}", warning)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the warnings, star rating, pp values and timings of the map that will be generated.
+fn print_map_info(map: &MapOptions, settings: &Settings) -> Result<()>{
+    // Errors are returned by generate_map, so only the warnings are printed here
+    for issue in map.validate(settings).iter().filter(|x| x.severity == Severity::Warning){
+        writeln!(stderr(), "Warning: {}", issue)?;
+    }
+    match map.performance(settings){
+        Ok(performance) => {
+            if map.changes_objects(){
+                println!("Star rating and pp are calculated from the difficulty values only, without the changes to the objects of the map.");
+            }
+            println!("Star rating: {} (original: {})", performance.stars, map.stars);
+            for (accuracy, pp) in Performance::ACCURACIES.iter().zip(performance.pp){
+                if *accuracy == 100.0{
+                    println!("SS: {}pp", pp);
+                }else{
+                    println!("{}%: {}pp", accuracy, pp);
+                }
+            }
+            println!("{}% {}x: {}pp", settings.pp_accuracy, settings.pp_misses, performance.custom_pp);
+        },
+        Err(e) => writeln!(stderr(), "Warning: Could not calculate the star rating and pp: {}", e)?
+    }
+    let timing = map.timing();
+    if let (Some(preempt), Some(ar)) = (timing.preempt, timing.effective_ar()){
+        println!("Approach: {}ms (AR {}, {}ms in the map file)", timing.effective(preempt), ar, round_dec(preempt, 2));
+    }
+    if let Some(fade_in) = timing.fade_in{
+        println!("Fade in: {}ms ({}ms in the map file)", timing.effective(fade_in), round_dec(fade_in, 2));
+    }
+    if let Some(od) = timing.effective_od(){
+        let windows = timing.hit_windows.iter().map(|(name, window)| format!("{}: ±{}ms", name, timing.effective(*window))).collect::<Vec<String>>();
+        println!("Hit windows: {} (OD {})", windows.join(", "), od);
+    }
+    Ok(())
+}
+
+/// Checks whether the given maps can be generated without losing any of their content.
+fn verify(paths: &[&str]) -> Result<()>{
+    if paths.is_empty(){
+        return Err(anyhow!("'verify' requires the path of at least one map."));
+    }
+    let settings = Settings::new_from_config();
+    let mut lossy_maps = 0;
+    for path in paths{
+        let temp_path = PathBuf::from(path);
+        let path = if temp_path.exists() { temp_path } else { settings.songs_path.join(&temp_path) };
+        let losses = verify_map(&path).map_err(|e| anyhow!("Could not verify '{}': {}", path.display(), e))?;
+        if losses.is_empty(){
+            println!("{}: OK", path.display());
+        }else{
+            // Only lost content prevents a map from being generated, other losses change how the map is written.
+            // Maps are verified as they are generated by default, which keeps their storyboard
+            if losses.iter().any(|x| x.is_content(false)){
+                lossy_maps += 1;
+            }
+            println!("{}:", path.display());
+            for loss in losses{
+                if loss.is_content(false){
+                    println!("  {}", loss);
+                }else{
+                    println!("  Warning: {}", loss);
+                }
+            }
+        }
+    }
+    if lossy_maps > 0{
+        return Err(anyhow!("{} of {} maps would lose content when generated.", lossy_maps, paths.len()));
+    }
+    Ok(())
+}
+
+/// Prints the difficulty values and statistics of a map next to those of a map generated from it.
+fn compare(paths: &[&str]) -> Result<()>{
+    let [original, generated] = paths else {
+        return Err(anyhow!("'compare' requires the path of the original map and the path of the generated map."));
+    };
+    let settings = Settings::new_from_config();
+    let read_stats = |path: &str|{
+        let temp_path = PathBuf::from(path);
+        let path = if temp_path.exists() { temp_path } else { settings.songs_path.join(&temp_path) };
+        MapStats::from_file(&path).map_err(|e| anyhow!("Could not read '{}': {}", path.display(), e))
+    };
+    let (original, generated) = (read_stats(original)?, read_stats(generated)?);
+    println!("{:<12}{:>12}{:>12}", "", "Original", "Generated");
+    for (name, original, generated) in original.rows(&generated){
+        println!("{:<12}{:>12}{:>12}", name, original, generated);
+    }
+    Ok(())
+}
+
 fn print_help(){
     const BOLD: &str = "\x1b[1m";
     const UND: &str = "\x1b[4m";
@@ -231,58 +541,136 @@ fn print_help(){
     if stdout().is_terminal(){
         println!("{}Generates osu! maps based on given args.", BOLD);
         println!("{}Running with no arguments runs the GUI version.", BOLD);
-        println!("{}{}Usage:{}{} ruso [OPTIONS]{}\n", BOLD, UND, RES, BOLD, RES);
+        println!("{}{}Usage:{}{} ruso [OPTIONS]{}", BOLD, UND, RES, BOLD, RES);
+        println!("       {}ruso verify [MAPS]...{}  Check whether maps can be generated without losing any of their content.", BOLD, RES);
+        println!("       {}ruso compare [ORIGINAL] [GENERATED]{}  Compare the difficulty of a generated map with its original map.\n", BOLD, RES);
         println!("{}{}OPTIONS:{}\n", BOLD, UND, RES);
-        println!("{BOLD}Options that can be enabled or disabled with (-/+) will use their respective values in '{}' if left unspecified.{RES}\n", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("{BOLD}Options that can be enabled or disabled with (-/+) will use their respective values in '{}' if left unspecified.{RES}", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("{BOLD}The map overrides saved in the same file are applied to every generated map.{RES}\n");
         println!("  {}-h, --help                      {}Print the help information and exit.", BOLD, RES);
         println!("  {}-V, --version                   {}Print version and exit.", BOLD, RES);
         println!("  {}-a, --approach-rate      [AR]   {}The approach rate of the map. Will remain unchanged if not provided.", BOLD, RES);
+        println!("                                    Use '@10.3' for the approach rate experienced at the chosen rate or '450ms' for the approach time at the chosen rate.");
         println!("  {}-/+A                            {}Enable (+A) or disable (-A) approach scaling with rate. Scaling will not work if '-a' is specified.", BOLD, RES);
-        println!("  {}-b, --bpm                [BPM]  {}The new bpm of the map. This will override '--rate' if provided.", BOLD, RES);
+        println!("  {}-b, --bpm                [BPM]  {}The new dominant bpm of the map. This will override '--rate' if provided.", BOLD, RES);
+        println!("  {}-B, --blur               [BLUR] {}Blur the background of the generated map. Higher values blur more.", BOLD, RES);
         println!("  {}-c, --circle-size        [CS]   {}The circle size of the map. Will remain unchanged if not provided.", BOLD, RES);
+        println!("                                    For osu!mania maps this is the key count, and the notes will be moved to the new columns.");
         println!("  {}-C, --clean                     {}Remove ALL maps and audio files generated by ruso and exit.", BOLD, RES);
         println!("  {}-d, --hp-drain           [HP]   {}The hp drain of the map. Will remain unchanged if not provided.", BOLD, RES);
+        println!("  {}-D, --dim                [DIM]  {}Darken the background of the generated map by the given percentage.", BOLD, RES);
+        println!("  {}-e, --no-storyboard             {}Remove the video and storyboard of the generated map. The storyboard shared by the whole mapset (.osb file) is left out of the .osz file, but osu! still shows it with the generated map.", BOLD, RES);
         println!("  {}-/+f                            {}Enable (+f) or disable (-f) forcing the generation of audio files even if they already exist.", BOLD, RES);
+        println!("  {}-/+F                            {}Enable (+F) or disable (-F) generating maps even if content of the original map that ruso can not preserve would be lost.", BOLD, RES);
         println!("  {}-g, --gosumemory                {}Spawn gosumemory as a child process.", BOLD, RES);
         println!("                                    This will use the paths provided in '{}' as the gosumemory and osu! songs path respectively.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("  {}-G, --ln-gap             [DIV]  {}The gap left before the next note by '--long-notes full' and '--long-notes invert' in 1/DIV of a beat. Defaults to 4.", BOLD, RES);
+        println!("  {}-H, --downscale          [PX]   {}Downscale the background of the generated map if it is taller than the given height in pixels.", BOLD, RES);
+        println!("  {}-i, --accuracy           [ACC]  {}The accuracy of the custom pp value printed for the generated map. Defaults to the value in the settings.", BOLD, RES);
+        println!("  {}-I, --background         [BG]   {}Replace the background of the generated map with a colour ('#rrggbb' or 'r,g,b') or an image.", BOLD, RES);
+        println!("  {}-j, --json                      {}Print the metadata, difficulty attributes, pp values, bpm and output paths of the maps as JSON instead of generating them.", BOLD, RES);
+        println!("  {}-k, --tick-rate          [TR]   {}The slider tick rate of the map. Will remain unchanged if not provided.", BOLD, RES);
+        println!("  {}-K, --stack-leniency     [SL]   {}The stack leniency of the map. Will remain unchanged if not provided.", BOLD, RES);
+        println!("  {}-l, --sliders            [MODE] {}Keep sliders (keep), convert them to circles (circles) or to circles with an extra circle at the slider end (tails).", BOLD, RES);
+        println!("  {}-L, --long-notes         [MODE] {}Keep the long notes of an osu!mania map (keep), turn every note into a long note (full),", BOLD, RES);
+        println!("                                    turn every long note into a regular note (none) or invert the long notes (invert).");
+        println!("  {}-m, --mods               [MODS] {}Bake the given mods into the map so it can be played without them, e.g. 'HRDT'.", BOLD, RES);
+        println!("                                    Supported mods are EZ, HR, HT and DT.");
+        println!("  {}-M, --mirror             [AXIS] {}Mirror an osu!standard map horizontally (h), vertically (v) or along both axes (d).", BOLD, RES);
+        println!("                                    osu!mania maps can only be mirrored horizontally, which mirrors their columns.");
+        println!("  {}-n, --spinners           [MODE] {}Keep spinners (keep), remove them (remove) or shorten them to the given length in milliseconds.", BOLD, RES);
+        println!("  {}-N, --misses             [MISS] {}The amount of misses of the custom pp value printed for the generated map. Defaults to the value in the settings.", BOLD, RES);
         println!("  {}-o, --overall-difficulty [OD]   {}The overall difficulty of the map. Will remain unchanged if not provided.", BOLD, RES);
+        println!("                                    Use '@11' for the overall difficulty experienced at the chosen rate or '20ms' for the best hit window at the chosen rate.");
         println!("  {}-/+O                            {}Enable (+O) or disable (-O) overall difficulty with rate. Scaling will not work if '-o' is specified.", BOLD, RES);
-        println!("  {}-p, --path               [PATH] {}The path to the osu! map.", BOLD, RES);
+        println!("  {}-p, --path               [PATH] {}The path to the osu! map. Can be given multiple times to use the same options for several maps.", BOLD, RES);
         println!("                                    This can be a regular path or a path the osu! songs path provided in '{}' as the root.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
         println!("                                    This is inferred, and the former will take precedence over the latter.");
         println!("                                    If this is not provided, ruso will attempt to connect to a running gosumemory instance with the websocket url provided in '{}'.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("  {}-q, --bpm-target         [TGT]  {}Which bpm of the map '--bpm' changes: the dominant (dominant), lowest (min) or highest (max) bpm. Defaults to dominant.", BOLD, RES);
         println!("  {}-/+P                            {}Enable (+P) or disable (-P) the rate of a song affecting its pitch.", BOLD, RES);
         println!("  {}-r, --rate               [RATE] {}The playback rate (or speed) of the map.", BOLD, RES);
         println!("                                    This will speed up the .osu file and the corresponding audio file.");
+        println!("  {}-R, --rotate             [DEG]  {}Rotate an osu!standard map clockwise around the center of the playfield.", BOLD, RES);
+        println!("  {}-s, --spacing            [MULT] {}Multiply the distance between the hit objects of an osu!standard map.", BOLD, RES);
+        println!("  {}-x, --random             [SEED] {}Randomize the angles between the hit objects of an osu!standard map with the given seed.", BOLD, RES);
+        println!("                                    The columns of osu!mania maps are shuffled instead.");
+        println!("                                    Use 'new' to generate a new seed. The seed is saved in the difficulty name and tags of the map.");
+        println!("  {}-y, --stars              [SR]   {}Search for the rate at which the generated map has the given star rating. This will override '--rate' and '--bpm'.", BOLD, RES);
+        println!("                                    AR and OD are scaled with the rate unless scaling is disabled or they are provided.");
+        println!("  {}-S, --slider-velocity    [SV]   {}The slider velocity multiplier of the map, between 0.4 and 3.6. Sliders keep their duration, so they are cut short along their path or extended past their end. Will remain unchanged if not provided.", BOLD, RES);
+        println!("  {}-t, --thin-streams       [DIV]  {}Remove every other note from streams snapped to 1/DIV of a beat or faster.", BOLD, RES);
+        println!("  {}-T, --convert            [MODE] {}Convert an osu!standard map to another mode (osu, taiko, catch or mania) like osu! does for converted maps.", BOLD, RES);
         println!("  {}-u, --used-space                {}Print the amount of space used by maps generated by ruso in bytes and exit.", BOLD, RES);
+        println!("  {}-w, --ladder             [LADR] {}Generate a practice map for each step of a range of rates (rate), approach rates (ar) or overall difficulties (od)", BOLD, RES);
+        println!("                                    into one mapset, e.g. 'rate:1-1.3:0.05' or 'ar:8-10:0.5'. The step defaults to 0.05 for rates and 0.5 otherwise.");
         println!("  {}-/+z                            {}Enable (+z) or disable (-z) generation of .osz files.", BOLD, RES);
     }else{
         println!("Generates osu! maps based on given args.");
         println!("Running with no arguments runs the GUI version.");
-        println!("Usage: ruso [OPTIONS]\n");
+        println!("Usage: ruso [OPTIONS]");
+        println!("       ruso verify [MAPS]...  Check whether maps can be generated without losing any of their content.");
+        println!("       ruso compare [ORIGINAL] [GENERATED]  Compare the difficulty of a generated map with its original map.\n");
         println!("OPTIONS:\n");
-        println!("Options that can be enabled or disabled with (-/+) will use their respective values in '{}' if left unspecified.\n", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("Options that can be enabled or disabled with (-/+) will use their respective values in '{}' if left unspecified.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("The map overrides saved in the same file are applied to every generated map.\n");
         println!("  -h, --help                      Print the help information and exit.");
         println!("  -V, --version                   Print version and exit.");
         println!("  -a, --approach-rate      [AR]   The approach rate of the map. Will remain unchanged if not provided.");
+        println!("                                  Use '@10.3' for the approach rate experienced at the chosen rate or '450ms' for the approach time at the chosen rate.");
         println!("  -/+A                            Enable (+A) or disable (-A) approach scaling with rate. Scaling will not work if '-a' is specified.");
-        println!("  -b, --bpm                [BPM]  The new bpm of the map. This will override '--rate' if provided.");
+        println!("  -b, --bpm                [BPM]  The new dominant bpm of the map. This will override '--rate' if provided.");
+        println!("  -B, --blur               [BLUR] Blur the background of the generated map. Higher values blur more.");
         println!("  -c, --circle-size        [CS]   The circle size of the map. Will remain unchanged if not provided.");
+        println!("                                  For osu!mania maps this is the key count, and the notes will be moved to the new columns.");
         println!("  -C, --clean                     Remove ALL maps and audio files generated by ruso and exit.");
         println!("  -d, --hp-drain           [HP]   The hp drain of the map. Will remain unchanged if not provided.");
+        println!("  -D, --dim                [DIM]  Darken the background of the generated map by the given percentage.");
+        println!("  -e, --no-storyboard             Remove the video and storyboard of the generated map. The storyboard shared by the whole mapset (.osb file) is left out of the .osz file, but osu! still shows it with the generated map.");
         println!("  -/+f                            Enable (+f) or disable (-f) forcing the generation of audio files even if they already exist.");
+        println!("  -/+F                            Enable (+F) or disable (-F) generating maps even if content of the original map that ruso can not preserve would be lost.");
         println!("  -g, --gosumemory                Spawn gosumemory as a child process.");
         println!("                                  This will use the paths provided in '{}' as the gosumemory and osu! songs path respectively.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("  -G, --ln-gap             [DIV]  The gap left before the next note by '--long-notes full' and '--long-notes invert' in 1/DIV of a beat. Defaults to 4.");
+        println!("  -H, --downscale          [PX]   Downscale the background of the generated map if it is taller than the given height in pixels.");
+        println!("  -i, --accuracy           [ACC]  The accuracy of the custom pp value printed for the generated map. Defaults to the value in the settings.");
+        println!("  -I, --background         [BG]   Replace the background of the generated map with a colour ('#rrggbb' or 'r,g,b') or an image.");
+        println!("  -j, --json                      Print the metadata, difficulty attributes, pp values, bpm and output paths of the maps as JSON instead of generating them.");
+        println!("  -k, --tick-rate          [TR]   The slider tick rate of the map. Will remain unchanged if not provided.");
+        println!("  -K, --stack-leniency     [SL]   The stack leniency of the map. Will remain unchanged if not provided.");
+        println!("  -l, --sliders            [MODE] Keep sliders (keep), convert them to circles (circles) or to circles with an extra circle at the slider end (tails).");
+        println!("  -L, --long-notes         [MODE] Keep the long notes of an osu!mania map (keep), turn every note into a long note (full),");
+        println!("                                  turn every long note into a regular note (none) or invert the long notes (invert).");
+        println!("  -m, --mods               [MODS] Bake the given mods into the map so it can be played without them, e.g. 'HRDT'.");
+        println!("                                  Supported mods are EZ, HR, HT and DT.");
+        println!("  -M, --mirror             [AXIS] Mirror an osu!standard map horizontally (h), vertically (v) or along both axes (d).");
+        println!("                                  osu!mania maps can only be mirrored horizontally, which mirrors their columns.");
+        println!("  -n, --spinners           [MODE] Keep spinners (keep), remove them (remove) or shorten them to the given length in milliseconds.");
+        println!("  -N, --misses             [MISS] The amount of misses of the custom pp value printed for the generated map. Defaults to the value in the settings.");
         println!("  -o, --overall-difficulty [OD]   The overall difficulty of the map. Will remain unchanged if not provided.");
+        println!("                                  Use '@11' for the overall difficulty experienced at the chosen rate or '20ms' for the best hit window at the chosen rate.");
         println!("  -/+O                            Enable (+O) or disable (-O) overall difficulty with rate. Scaling will not work if '-o' is specified.");
-        println!("  -p, --path               [PATH] The path to the osu! map.");
+        println!("  -p, --path               [PATH] The path to the osu! map. Can be given multiple times to use the same options for several maps.");
         println!("                                  This can be a regular path or a path the osu! songs path provided in '{}' as the root.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
         println!("                                  This is inferred, and the former will take precedence over the latter.");
         println!("                                  If this is not provided, ruso will attempt to connect to a running gosumemory instance with the websocket url provided in '{}'.", dirs::config_dir().unwrap_or(PathBuf::from("$CONFIGDIR")).join("ruso").join("settings.json").display());
+        println!("  -q, --bpm-target         [TGT]  Which bpm of the map '--bpm' changes: the dominant (dominant), lowest (min) or highest (max) bpm. Defaults to dominant.");
         println!("  -/+P                            Enable (+P) or disable (-P) the rate of a song affecting its pitch.");
         println!("  -r, --rate               [RATE] The playback rate (or speed) of the map.");
         println!("                                  This will speed up the .osu file and the corresponding audio file.");
+        println!("  -R, --rotate             [DEG]  Rotate an osu!standard map clockwise around the center of the playfield.");
+        println!("  -s, --spacing            [MULT] Multiply the distance between the hit objects of an osu!standard map.");
+        println!("  -x, --random             [SEED] Randomize the angles between the hit objects of an osu!standard map with the given seed.");
+        println!("                                  The columns of osu!mania maps are shuffled instead.");
+        println!("                                  Use 'new' to generate a new seed. The seed is saved in the difficulty name and tags of the map.");
+        println!("  -y, --stars              [SR]   Search for the rate at which the generated map has the given star rating. This will override '--rate' and '--bpm'.");
+        println!("                                  AR and OD are scaled with the rate unless scaling is disabled or they are provided.");
+        println!("  -S, --slider-velocity    [SV]   The slider velocity multiplier of the map, between 0.4 and 3.6. Sliders keep their duration, so they are cut short along their path or extended past their end. Will remain unchanged if not provided.");
+        println!("  -t, --thin-streams       [DIV]  Remove every other note from streams snapped to 1/DIV of a beat or faster.");
+        println!("  -T, --convert            [MODE] Convert an osu!standard map to another mode (osu, taiko, catch or mania) like osu! does for converted maps.");
         println!("  -u, --used-space                Print the amount of space used by maps generated by ruso in bytes and exit.");
+        println!("  -w, --ladder             [LADR] Generate a practice map for each step of a range of rates (rate), approach rates (ar) or overall difficulties (od)");
+        println!("                                  into one mapset, e.g. 'rate:1-1.3:0.05' or 'ar:8-10:0.5'. The step defaults to 0.05 for rates and 0.5 otherwise.");
         println!("  -/+z                            Enable (+z) or disable (-z) generation of .osz files.");
     }
 }
@@ -0,0 +1,119 @@
+use std::{path::Path, sync::Arc};
+use anyhow::Result;
+use rosu_pp::{BeatmapExt, DifficultyAttributes, parse::HitObjectKind};
+use crate::{structs::{game_mode, LoadedMap, MapOptions, Settings}, utils::{format_time, round_dec}};
+
+/// Difficulty values and statistics of a map, used to compare a generated map with its original
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MapStats{
+    pub approach_rate: f64,
+    pub circle_size: f64,
+    pub hp_drain: f64,
+    pub overall_difficulty: f64,
+    pub min_bpm: f64,
+    pub max_bpm: f64,
+    /// Time in milliseconds from the start of the first hit object to the end of the last one
+    pub length: f64,
+    /// Length without breaks in milliseconds
+    pub drain_time: f64,
+    pub circles: usize,
+    /// Sliders, or hold notes in osu!mania
+    pub sliders: usize,
+    pub spinners: usize,
+    pub stars: f64,
+    /// Aim, speed and flashlight rating of osu!standard maps
+    pub skills: Option<(f64, f64, f64)>,
+    /// pp value for an SS
+    pub pp: f64,
+}
+
+impl MapStats{
+    /// Reads the stats of a .osu file, such as a generated map.
+    pub fn from_file(path: &Path) -> Result<Self>{
+        Ok(Self::from_loaded(LoadedMap::load(path)?))
+    }
+
+    /// Reads the stats of the contents of a .osu file, such as a generated map that was only written to an .osz file.
+    pub fn from_contents(path: &Path, source: &str) -> Result<Self>{
+        Ok(Self::from_loaded(LoadedMap::parse(path, source.to_string())?))
+    }
+
+    fn from_loaded(loaded: LoadedMap) -> Self{
+        let loaded = Arc::new(loaded);
+        let mut options = MapOptions::new();
+        options.map_path = loaded.path.clone();
+        options.read_loaded_metadata(loaded.clone(), &Settings::new());
+        Self::calculate(&options, &loaded.difficulty, 1.0)
+    }
+
+    /// Calculates the stats the map would have if it was generated with the options, without generating it.
+    /// Changes to the hit objects, such as removed sliders or spinners, are not included in the object counts.
+    pub fn from_options(map: &MapOptions, settings: &Settings) -> Result<Self>{
+        Ok(Self::calculate(map, &map.difficulty_beatmap(settings)?, map.effective_rate()))
+    }
+
+    fn calculate(map: &MapOptions, beatmap: &rosu_pp::Beatmap, rate: f64) -> Self{
+        let mode = game_mode(map.target_mode());
+        let difficulty = beatmap.stars().mode(mode).clock_rate(rate).calculate();
+        let pp = beatmap.pp().mode(mode).clock_rate(rate).attributes(difficulty.clone()).calculate().pp();
+        // The flashlight rating is only calculated with the flashlight mod, which does not change the other ratings
+        let skills = match beatmap.stars().mode(mode).clock_rate(rate).mods(1 << 10).calculate(){
+            DifficultyAttributes::Osu(x) => Some((round_dec(x.aim, 2), round_dec(x.speed, 2), round_dec(x.flashlight, 2))),
+            _ => None
+        };
+
+        let (mut circles, mut sliders, mut spinners) = (0, 0, 0);
+        for h in &beatmap.hit_objects{
+            match h.kind{
+                HitObjectKind::Circle => circles += 1,
+                HitObjectKind::Slider{ .. } | HitObjectKind::Hold{ .. } => sliders += 1,
+                HitObjectKind::Spinner{ .. } => spinners += 1,
+            }
+        }
+        let start = beatmap.hit_objects.first().map_or(0.0, |h| h.start_time);
+        let end = beatmap.hit_objects.iter().map(|h| h.end_time()).fold(start, f64::max);
+        let breaks: f64 = beatmap.breaks.iter().map(|x| x.end_time - x.start_time).sum();
+
+        MapStats{
+            approach_rate: map.approach_rate,
+            circle_size: map.circle_size,
+            hp_drain: map.hp_drain,
+            overall_difficulty: map.overall_difficulty,
+            min_bpm: round_dec(map.bpm_info.min * rate, 2),
+            max_bpm: round_dec(map.bpm_info.max * rate, 2),
+            length: (end - start) / rate,
+            drain_time: (end - start - breaks).max(0.0) / rate,
+            circles,
+            sliders,
+            spinners,
+            stars: round_dec(difficulty.stars(), 2),
+            skills,
+            pp: round_dec(pp, 2),
+        }
+    }
+
+    /// Names of the stats with the formatted values of both maps, in the order they are shown.
+    pub fn rows(&self, other: &MapStats) -> Vec<(&'static str, String, String)>{
+        let bpm = |x: &MapStats| if x.min_bpm == x.max_bpm { x.min_bpm.to_string() } else { format!("{}-{}", x.min_bpm, x.max_bpm) };
+        let mut rows = vec![
+            ("AR", self.approach_rate.to_string(), other.approach_rate.to_string()),
+            ("CS", self.circle_size.to_string(), other.circle_size.to_string()),
+            ("HP", self.hp_drain.to_string(), other.hp_drain.to_string()),
+            ("OD", self.overall_difficulty.to_string(), other.overall_difficulty.to_string()),
+            ("BPM", bpm(self), bpm(other)),
+            ("Length", format_time(self.length as i32), format_time(other.length as i32)),
+            ("Drain time", format_time(self.drain_time as i32), format_time(other.drain_time as i32)),
+            ("Circles", self.circles.to_string(), other.circles.to_string()),
+            ("Sliders", self.sliders.to_string(), other.sliders.to_string()),
+            ("Spinners", self.spinners.to_string(), other.spinners.to_string()),
+            ("Stars", self.stars.to_string(), other.stars.to_string()),
+        ];
+        if let (Some((aim, speed, flashlight)), Some((other_aim, other_speed, other_flashlight))) = (self.skills, other.skills){
+            rows.push(("Aim", aim.to_string(), other_aim.to_string()));
+            rows.push(("Speed", speed.to_string(), other_speed.to_string()));
+            rows.push(("Flashlight", flashlight.to_string(), other_flashlight.to_string()));
+        }
+        rows.push(("pp", self.pp.to_string(), other.pp.to_string()));
+        rows
+    }
+}
@@ -1,66 +1,99 @@
 #![allow(non_snake_case)]
-use std::{path::PathBuf, time::Duration, io::ErrorKind};
+use std::{path::PathBuf, time::Duration, io::ErrorKind, sync::Arc};
 use dioxus::prelude::*;
 use include_base64::include_base64_std;
 use tokio_tungstenite::{connect_async, tungstenite::Error};
 use serde_json::from_str;
 use rfd::FileDialog;
 use libosu::data::Mode;
-use crate::{props::{SliderProps, ToggleableProps}, structs::{MapOptions, Settings, Status, StatusMessage, Theme, Tab}, utils::*};
+use crate::{compare::MapStats, ladder::{Ladder, LadderValue}, props::{SliderProps, ToggleableProps}, strains::{self, GRAPH_COLUMNS, StrainGraph}, timing::{DifficultyTarget, TimedValue}, structs::{BackgroundReplacement, Colour, LoadedMap, LongNotes, MapField, MapOptions, Performance, Mirror, Settings, Spinners, Status, StatusMessage, Theme, Tab}, utils::*};
 use futures_util::StreamExt;
 
 pub fn GenericSlider<'a>(cx: Scope<'a, SliderProps<'a>>) -> Element{
+    let msg = use_shared_state::<StatusMessage>(cx)?;
+    let clamp = |val: f64| round_dec(val, 2).min(cx.props.max).max(cx.props.min);
+
+    // The number input can show the value in the map, the value the player experiences at the rate or the
+    // milliseconds the player experiences at the rate
+    let input_mode = use_state(cx, || DifficultyTarget::Value(0.0));
+    let effective = cx.props.timed.and_then(|(timed, rate)| timed.effective(cx.props.read, rate));
+    let (input_value, input_step, mode_label) = match (*input_mode.get(), effective){
+        (DifficultyTarget::Effective(_), Some((value, _))) => (value, cx.props.step, "@"),
+        (DifficultyTarget::Milliseconds(_), Some((_, ms))) => (ms, 1.0, "ms"),
+        _ => (cx.props.read, cx.props.step, "Map"),
+    };
+    let set_input = move |input: f64|{
+        let target = input_mode.get().with_value(input);
+        let value = match (cx.props.timed, effective){
+            (Some((timed, rate)), Some(_)) if target != DifficultyTarget::Value(input) => timed.map_value(target, rate),
+            _ => None
+        };
+        let Some(value) = value else {
+            cx.props.on_event.call(clamp(input));
+            return;
+        };
+        if !(cx.props.min..=cx.props.max).contains(&round_dec(value, 2)){
+            msg.write().text = Some(format!("Warning: {} {} needs {} {} in the map, using {} instead", cx.props.acronym, target, cx.props.acronym, round_dec(value, 2), clamp(value)));
+            msg.write().status = Status::Error;
+        }
+        cx.props.on_event.call(clamp(value));
+    };
+    let (container_class, title) = if cx.props.disabled{
+        ("slider-container disabled", format!("{} (no effect on this mode)", cx.props.name))
+    }else{
+        ("slider-container", cx.props.name.to_string())
+    };
     cx.render(rsx! {
         div {
-            class: "slider-container",
-            title: "{cx.props.name}",
+            class: "{container_class}",
+            title: "{title}",
             span{ class: "slider-label", "{cx.props.acronym}"}
             input {
                 r#type: "range",
-                min: 0,
-                max: 100,
-                value: "{cx.props.read * 10.0}",
+                min: "{cx.props.min}",
+                max: "{cx.props.max}",
+                step: "{cx.props.step}",
+                value: "{cx.props.read}",
                 class: "slider generic-slider",
                 id: "{cx.props.acronym}",
                 onwheel: move |ev|{
-                    let mut temp_val = round_dec(cx.props.read - (ev.data.delta().strip_units().y.signum()/10.0), 2);
-                    if temp_val > 10.0 {
-                        temp_val  = 10.0;
-                    } else if temp_val < 0.0 {
-                        temp_val = 0.0;
-                    }                    
-                    cx.props.on_event.call(temp_val);
+                    cx.props.on_event.call(clamp(cx.props.read - ev.data.delta().strip_units().y.signum() * cx.props.step));
                 },
                 oninput: move |ev|{
-                    cx.props.on_event.call(ev.data.value.parse::<f64>().unwrap() / 10.0);
+                    cx.props.on_event.call(ev.data.value.parse::<f64>().unwrap());
                 },
             }
             input { 
                 r#type: "number",
-                min: 0,
-                max: 10,
-                step: 0.1,
-                value: "{cx.props.read}",
+                step: "{input_step}",
+                value: "{input_value}",
                 id: "{cx.props.acronym}_number",
                 onwheel: move |ev|{
-                    let mut temp_val = round_dec(cx.props.read - (ev.data.delta().strip_units().y.signum()/10.0), 2);
-                    if temp_val > 10.0 {
-                        temp_val  = 10.0;
-                    } else if temp_val < 0.0 {
-                        temp_val = 0.0;
-                    }                    
-                    cx.props.on_event.call(temp_val);
+                    set_input(input_value - ev.data.delta().strip_units().y.signum() * input_step);
                 },
                 onchange: move |ev|{
-                    let mut temp_val = ev.data.value.parse::<f64>().unwrap_or(cx.props.read);
-                    if temp_val > 10.0 {
-                        temp_val  = 10.0;
-                    } else if temp_val < 0.0 {
-                        temp_val = 0.0;
-                    }                    
-                    cx.props.on_event.call(temp_val);
+                    set_input(ev.data.value.parse::<f64>().unwrap_or(input_value));
                 },
             }
+            if effective.is_some(){
+                rsx!{
+                    button{
+                        class: "input-mode-button",
+                        title: "Input mode: Switches between the {cx.props.acronym} in the map (Map), the {cx.props.acronym} experienced at the chosen rate (@) and the milliseconds experienced at the chosen rate (ms)",
+                        onclick: move |_| input_mode.set(match *input_mode.get(){
+                            DifficultyTarget::Value(_) => DifficultyTarget::Effective(0.0),
+                            DifficultyTarget::Effective(_) => DifficultyTarget::Milliseconds(0.0),
+                            DifficultyTarget::Milliseconds(_) => DifficultyTarget::Value(0.0),
+                        }),
+                        "{mode_label}"
+                    }
+                }
+            }
+            if let Some(note) = &cx.props.note{
+                rsx!{
+                    span{ class: "slider-note", title: "{note}", "!" }
+                }
+            }
             div{
                 class: "lock-container",
                 title: "{cx.props.name} Lock: Locks the {cx.props.acronym} slider to the current value, preventing it from being changed by loading a new map",
@@ -82,8 +115,17 @@ pub fn GenericSlider<'a>(cx: Scope<'a, SliderProps<'a>>) -> Element{
 }
 
 #[inline_props]
-pub fn RateSlider<'a>(cx: Scope, on_event: EventHandler<'a, f64>, bpm: usize) -> Element{
+pub fn RateSlider<'a>(cx: Scope, on_event: EventHandler<'a, f64>, rate: f64, bpm: usize, min_bpm: usize, max_bpm: usize) -> Element{
     let value = use_state(cx, || 1.0);
+    // Keep the slider in sync when the rate is changed from somewhere else, e.g. the star rating solver
+    use_effect(cx, (rate,), |(rate,)|{
+        to_owned![value];
+        async move{
+            if *value.get() != rate{
+                value.set(rate);
+            }
+        }
+    });
     let new_bpm = (*bpm as f64 * *value.get()).round() as usize;
     let settings = use_shared_state::<Settings>(cx)?;
     
@@ -154,6 +196,7 @@ pub fn RateSlider<'a>(cx: Scope, on_event: EventHandler<'a, f64>, bpm: usize) ->
                 }
                 div{
                     class: "bpm-input",
+                    title: "Dominant BPM: {bpm}, ranges from {min_bpm} to {max_bpm}",
                     "{bpm}"
                 }
                 div{
@@ -398,6 +441,20 @@ pub fn SettingsTab(cx: Scope) -> Element{
                 }
             }
             br {}
+            div{
+                class: "option-container",
+                title: "Allow lossy generation: If checked, ruso generates maps even if content of the original map that ruso can not preserve, such as unknown sections or keys, would be lost",
+                "Allow lossy generation "
+                input {
+                    r#type: "checkbox",
+                    checked: "{settings.read().allow_lossy}",
+                    onclick: move |_| {
+                        let temp = settings.read().allow_lossy;
+                        settings.write().allow_lossy = !temp;
+                    }
+                }
+            }
+            br {}
             div{
                 class: "option-container",
                 title: "osu! songs path: This is the path to your osu! songs folder",
@@ -422,6 +479,8 @@ pub fn SettingsTab(cx: Scope) -> Element{
                 }
             }
             br {}
+            OverridesComponent{}
+            br {}
             p{ style: "display: inline;", title: "Space used: The amount of space used by the .osu and audio files generated by ruso", "Space used {used_space_pretty}" }
             br {}
             div{
@@ -538,9 +597,16 @@ pub fn AutoTab(cx: Scope) -> Element{
                                         if settings.read().songs_path == PathBuf::new() {
                                             settings.write().songs_path = PathBuf::from(data["settings"]["folders"]["songs"].as_str().unwrap());
                                         }
-                                        if let Err(e) = map.write().read_map_metadata(&settings.read()){
-                                            msg.write().text = Some(format!("Error reading map metadata: {}", e));
-                                            msg.write().status = Status::Error;
+                                        // Load the map on another thread so quickly changing maps does not stall the UI
+                                        let path = settings.read().songs_path.join(&map.read().map_path);
+                                        let loaded = tokio::task::spawn_blocking(move || LoadedMap::load(&path)).await
+                                            .unwrap_or_else(|e| Err(e.into()));
+                                        match loaded{
+                                            Ok(loaded) => map.write().read_loaded_metadata(Arc::new(loaded), &settings.read()),
+                                            Err(e) => {
+                                                msg.write().text = Some(format!("Error reading map metadata: {}", e));
+                                                msg.write().status = Status::Error;
+                                            }
                                         }
                                     }
                                 },
@@ -588,6 +654,34 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
     let msg = use_shared_state::<StatusMessage>(cx)?;
     let tab = use_shared_state::<Tab>(cx)?;
     let generating_map = use_state(cx, || false);
+    let comparison = use_state(cx, || None::<(MapStats, MapStats)>);
+
+    // Recalculate the star rating and pp of the generated map once the options stop changing
+    let performance = use_state(cx, || None::<Performance>);
+    let strain_graph = use_state(cx, || None::<StrainGraph>);
+    let performance_generation = use_ref(cx, || 0_usize);
+    let pp_options = (settings.read().pp_accuracy, settings.read().pp_misses);
+    use_effect(cx, (&map.read().clone(), &pp_options), |(map_options, _)|{
+        performance_generation.with_mut(|x| *x += 1);
+        let generation = *performance_generation.read();
+        to_owned![performance, strain_graph, performance_generation, settings];
+        async move{
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            if generation != *performance_generation.read() || map_options.title.is_empty(){
+                return;
+            }
+            performance.set(map_options.performance(&settings.read()).ok());
+            strain_graph.set(strains::strain_graph(&map_options, &settings.read()).ok());
+        }
+    });
+    let stars = performance.get().as_ref().map_or(map.read().stars, |x| x.stars);
+
+    // Validation messages shown next to the sliders of the respective fields
+    let issues = map.read().validate(&settings.read());
+    let field_note = |field: MapField|{
+        let messages = issues.iter().filter(|x| x.field == field).map(|x| x.to_string()).collect::<Vec<String>>();
+        if messages.is_empty() { None } else { Some(messages.join("\n")) }
+    };
 
     // Determine image path for background image
     let bg_path = use_memo(cx, &(map.read().background), |bg|{
@@ -603,7 +697,7 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
     });
 
     // Get image for respective osu! gamemode
-    let mode_img = match map.read().mode{
+    let mode_img = match map.read().target_mode(){
             Mode::Osu =>   concat!("data:image/png;base64,", include_base64_std!("./assets/standard.png")),
             Mode::Taiko => concat!("data:image/png;base64,", include_base64_std!("./assets/taiko.png")),
             Mode::Catch => concat!("data:image/png;base64,", include_base64_std!("./assets/catch.png")),
@@ -613,7 +707,6 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
     // Using css filters for the respective star range colors since I don't want to color the image
     // manually
     let css_filter = {
-            let stars = map.read().stars;
             match stars{
             _ if stars < 2.0 => "brightness(0) saturate(100%) invert(77%) sepia(49%) saturate(4262%) hue-rotate(176deg) brightness(102%) contrast(105%)",
             _ if stars < 2.7 => "brightness(0) saturate(100%) invert(87%) sepia(45%) saturate(722%) hue-rotate(43deg) brightness(101%) contrast(103%)",
@@ -671,8 +764,13 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                     }
                     div{
                         class: "map-stars",
-                        title: "Star Rating: {map.read().stars}",
-                        "{map.read().stars} "
+                        title: "Star Rating: {stars} (original: {map.read().stars})",
+                        "{stars} "
+                        if stars != map.read().stars{
+                            rsx!{
+                                span{ class: "original-stars", "({map.read().stars}) " }
+                            }
+                        }
                         img {
                             src: "{mode_img}",
                             width: "24px",
@@ -680,15 +778,43 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                             style: "filter: {css_filter}; margin-bottom: -6px;"
                         }
                     }
+                    if let Some(performance) = performance.get(){
+                        rsx!{
+                            div{
+                                class: "map-pp",
+                                title: "pp: The pp values of the generated map for the given accuracies",
+                                Performance::ACCURACIES.iter().zip(performance.pp).map(|(accuracy, pp)|{
+                                    let label = if *accuracy == 100.0 { "SS".to_string() } else { format!("{}%", accuracy) };
+                                    rsx!{ span{ key: "{accuracy}", "{label}: {pp}pp " } }
+                                })
+                                span{ "{settings.read().pp_accuracy}% {settings.read().pp_misses}x: {performance.custom_pp}pp" }
+                                if map.read().changes_objects(){
+                                    rsx!{
+                                        span{
+                                            class: "original-stars",
+                                            title: "The star rating and pp are calculated from the difficulty values only, without the changes to the objects of the map",
+                                            "(difficulty values only)"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
                 }
             }
         }
+        if let Some(graph) = strain_graph.get(){
+            rsx!{ StrainGraphComponent{ graph: graph.clone() } }
+        }
         div{
             GenericSlider {
                 name: "Approach Rate",
                 acronym: "AR",
                 read: map.read().approach_rate,
                 locked: settings.read().ar_lock,
+                disabled: !map.read().uses_field(MapField::ApproachRate),
+                note: field_note(MapField::ApproachRate),
+                timed: map.read().uses_field(MapField::ApproachRate).then(|| (TimedValue::ApproachRate, map.read().effective_rate())),
                 on_event: move |ev| map.write().approach_rate = ev,
                 on_lock: move |ev: bool| settings.write().ar_lock = !ev
             }
@@ -696,7 +822,11 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                 name: "Circle Size",
                 acronym: "CS",
                 read: map.read().circle_size,
+                min: if map.read().target_mode() == Mode::Mania { 1.0 } else { 0.0 },
+                step: if map.read().target_mode() == Mode::Mania { 1.0 } else { 0.1 },
                 locked: settings.read().cs_lock,
+                disabled: !map.read().uses_field(MapField::CircleSize),
+                note: field_note(MapField::CircleSize),
                 on_event: move |ev| map.write().circle_size = ev,
                 on_lock: move |ev: bool| settings.write().cs_lock = !ev
             }
@@ -705,6 +835,8 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                 acronym: "HP",
                 read: map.read().hp_drain,
                 locked: settings.read().hp_lock,
+                disabled: !map.read().uses_field(MapField::HpDrain),
+                note: field_note(MapField::HpDrain),
                 on_event: move |ev| map.write().hp_drain = ev,
                 on_lock: move |ev: bool| settings.write().hp_lock = !ev
             }
@@ -713,11 +845,59 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                 acronym: "OD",
                 read: map.read().overall_difficulty,
                 locked: settings.read().od_lock,
+                disabled: !map.read().uses_field(MapField::OverallDifficulty),
+                note: field_note(MapField::OverallDifficulty),
+                timed: map.read().uses_field(MapField::OverallDifficulty).then(|| (TimedValue::OverallDifficulty(map.read().target_mode()), map.read().effective_rate())),
                 on_event: move |ev| map.write().overall_difficulty = ev,
                 on_lock: move |ev: bool| settings.write().od_lock = !ev
             }
+            GenericSlider {
+                name: "Slider Velocity",
+                acronym: "SV",
+                read: map.read().slider_multiplier,
+                min: 0.4,
+                max: 3.6,
+                locked: settings.read().sm_lock,
+                disabled: !map.read().uses_field(MapField::SliderMultiplier),
+                note: field_note(MapField::SliderMultiplier),
+                on_event: move |ev| map.write().slider_multiplier = ev,
+                on_lock: move |ev: bool| settings.write().sm_lock = !ev
+            }
+            GenericSlider {
+                name: "Slider Tick Rate",
+                acronym: "TR",
+                read: map.read().slider_tick_rate,
+                min: 0.5,
+                max: 8.0,
+                step: 0.5,
+                locked: settings.read().tr_lock,
+                disabled: !map.read().uses_field(MapField::SliderTickRate),
+                note: field_note(MapField::SliderTickRate),
+                on_event: move |ev| map.write().slider_tick_rate = ev,
+                on_lock: move |ev: bool| settings.write().tr_lock = !ev
+            }
+            // Stacking only exists in osu!standard
+            if map.read().target_mode() == Mode::Osu{
+                rsx!{
+                    GenericSlider {
+                        name: "Stack Leniency",
+                        acronym: "SL",
+                        read: map.read().stack_leniency,
+                        min: 0.0,
+                        max: 1.0,
+                        step: 0.1,
+                        locked: settings.read().sl_lock,
+                        disabled: !map.read().uses_field(MapField::StackLeniency),
+                        note: field_note(MapField::StackLeniency),
+                        on_event: move |ev| map.write().stack_leniency = ev,
+                        on_lock: move |ev: bool| settings.write().sl_lock = !ev
+                    }
+                }
+            }
             RateSlider {
                 bpm: map.read().bpm,
+                min_bpm: map.read().bpm_info.min.round() as usize,
+                max_bpm: map.read().bpm_info.max.round() as usize,
                 on_event: move |ev| {
                     map.write().rate = ev;
                     if settings.read().scale_ar{
@@ -727,8 +907,25 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                         map.write().scale_od();
                     }
                 },
-                // rate: map.read().rate
+                rate: map.read().rate
+            }
+            StarTarget{}
+            LadderComponent{}
+            TimingInfo{}
+            ModsComponent{}
+            if map.read().mode == Mode::Osu{
+                rsx!{ ConvertOption{} }
+            }
+            if map.read().target_mode() == Mode::Osu{
+                rsx!{ TransformsComponent{} }
+            }
+            if map.read().target_mode() != Mode::Mania{
+                rsx!{ SimplifyComponent{} }
+            }else{
+                rsx!{ ManiaComponent{} }
             }
+            BackgroundComponent{}
+            PpOptions{}
         }
         div {
             class: "button-container",
@@ -741,13 +938,27 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                         generating_map.set(true);
                         msg.write().text = Some("Please wait, generating map...".to_string());
                         msg.write().status = Status::Success;
-                        to_owned![map, settings, msg, generating_map];
+                        to_owned![map, settings, msg, generating_map, comparison];
                         async move{
                             tokio::time::sleep(Duration::from_millis(100)).await; // Wait so the message can be displayed
                             match generate_map(&map.read(), &settings.read()).await{
-                                Ok(_) => {
-                                    msg.write().text = Some("Map created successfully!".to_string());
+                                Ok(generated) => {
+                                    // Changes to how the map is written and storyboards that are still shown do not prevent
+                                    // the map from being generated, but are pointed out
+                                    let warnings = map.read().validate(&settings.read()).into_iter()
+                                        .filter(|x| matches!(x.field, MapField::Fidelity | MapField::Storyboard))
+                                        .map(|x| x.to_string())
+                                        .collect::<Vec<String>>();
+                                    msg.write().text = Some(if warnings.is_empty(){
+                                        "Map created successfully!".to_string()
+                                    }else{
+                                        format!("Map created successfully! Note: {}", warnings.join(" "))
+                                    });
                                     msg.write().status = Status::Success;
+                                    // The generated map might only be in an .osz file, so its stats are read from its contents
+                                    let original = MapStats::from_file(&settings.read().songs_path.join(&map.read().map_path));
+                                    let generated = MapStats::from_contents(&generated.path, &generated.contents);
+                                    comparison.set(original.ok().zip(generated.ok()));
                                 },
                                 Err(e) => {
                                     msg.write().text = Some(format!("Error creating map: {}", e));
@@ -782,10 +993,1130 @@ pub fn MapOptionsComponent(cx: Scope) -> Element{
                 "Reset"
             }
         }
+        if let Some((original, generated)) = comparison.get(){
+            rsx!{
+                ComparisonView{
+                    original: original.clone(),
+                    generated: generated.clone(),
+                    on_close: move |_| comparison.set(None)
+                }
+            }
+        }
         MessageBox{}
     })
 }
 
+/// Toggles for the osu! mods that can be baked into the generated map
+pub fn ModsComponent(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let settings = use_shared_state::<Settings>(cx)?;
+
+    cx.render(rsx!{
+        div{
+            class: "mods-grid",
+            title: "Mods: Bakes the selected mods into the generated map so it can be played without them",
+            Toggleable{
+                name: "EZ",
+                title: "Easy: Halves the AR, CS, HP and OD of the map.",
+                toggled: map.read().mods.easy,
+                on_event: move |ev: bool| {
+                    let mut map = map.write();
+                    map.mods.easy = !ev;
+                    if !ev{
+                        map.mods.hard_rock = false;
+                    }
+                    map.apply_mods(&settings.read());
+                }
+            }
+            Toggleable{
+                name: "HR",
+                title: "Hard Rock: Flips the map vertically, multiplies CS by 1.3 and AR, HP and OD by 1.4. Only osu!standard maps are flipped.",
+                toggled: map.read().mods.hard_rock,
+                on_event: move |ev: bool| {
+                    let mut map = map.write();
+                    map.mods.hard_rock = !ev;
+                    if !ev{
+                        map.mods.easy = false;
+                    }
+                    map.apply_mods(&settings.read());
+                }
+            }
+            Toggleable{
+                name: "HT",
+                title: "Half Time: Slows the map down to 0.75x on top of the chosen rate, scaling AR and OD like osu! does.",
+                toggled: map.read().mods.half_time,
+                on_event: move |ev: bool| {
+                    let mut map = map.write();
+                    map.mods.half_time = !ev;
+                    if !ev{
+                        map.mods.double_time = false;
+                    }
+                    map.apply_mods(&settings.read());
+                }
+            }
+            Toggleable{
+                name: "DT",
+                title: "Double Time: Speeds the map up to 1.5x on top of the chosen rate, scaling AR and OD like osu! does.",
+                toggled: map.read().mods.double_time,
+                on_event: move |ev: bool| {
+                    let mut map = map.write();
+                    map.mods.double_time = !ev;
+                    if !ev{
+                        map.mods.half_time = false;
+                    }
+                    map.apply_mods(&settings.read());
+                }
+            }
+        }
+    })
+}
+
+/// Metadata and appearance overrides for generated maps, saved along with the other settings
+fn OverridesComponent(cx: Scope) -> Element{
+    let settings = use_shared_state::<Settings>(cx)?;
+    let overrides = settings.read().overrides.clone();
+    let text_override = |x: &str| if x.is_empty() { None } else { Some(x.to_string()) };
+
+    cx.render(rsx!{
+        h3 { title: "Map overrides: Replaces the metadata and appearance of generated maps. Empty fields are copied from the original map.", "Map overrides" }
+        div{
+            class: "overrides-grid",
+            div{
+                class: "option-container",
+                title: "Creator: Replaces the creator of generated maps",
+                "Creator "
+                input {
+                    r#type: "text",
+                    value: "{overrides.creator.clone().unwrap_or_default()}",
+                    oninput: move |ev| settings.write().overrides.creator = text_override(&ev.value)
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Source: Replaces the source of generated maps",
+                "Source "
+                input {
+                    r#type: "text",
+                    value: "{overrides.source.clone().unwrap_or_default()}",
+                    oninput: move |ev| settings.write().overrides.source = text_override(&ev.value)
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Tags: Replaces the tags of generated maps. Tags are separated by spaces",
+                "Tags "
+                input {
+                    r#type: "text",
+                    value: "{overrides.tags.clone().unwrap_or_default()}",
+                    oninput: move |ev| settings.write().overrides.tags = text_override(&ev.value)
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Unicode title: Replaces the unicode title of generated maps",
+                "Unicode title "
+                input {
+                    r#type: "text",
+                    value: "{overrides.title_unicode.clone().unwrap_or_default()}",
+                    oninput: move |ev| settings.write().overrides.title_unicode = text_override(&ev.value)
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Unicode artist: Replaces the unicode artist of generated maps",
+                "Unicode artist "
+                input {
+                    r#type: "text",
+                    value: "{overrides.artist_unicode.clone().unwrap_or_default()}",
+                    oninput: move |ev| settings.write().overrides.artist_unicode = text_override(&ev.value)
+                }
+            }
+            OverrideToggle{
+                name: "Countdown",
+                title: "Countdown: Shows or hides the countdown before the first hit object",
+                value: overrides.countdown,
+                on_event: move |ev| settings.write().overrides.countdown = ev
+            }
+            OverrideToggle{
+                name: "Letterbox in breaks",
+                title: "Letterbox in breaks: Shows or hides the letterbox during breaks",
+                value: overrides.letterbox_in_breaks,
+                on_event: move |ev| settings.write().overrides.letterbox_in_breaks = ev
+            }
+            OverrideToggle{
+                name: "Widescreen storyboard",
+                title: "Widescreen storyboard: Enables or disables the widescreen storyboard",
+                value: overrides.widescreen_storyboard,
+                on_event: move |ev| settings.write().overrides.widescreen_storyboard = ev
+            }
+            OverrideColour{
+                name: "Slider border",
+                title: "Slider border: Replaces the slider border colour of generated maps",
+                value: overrides.slider_border,
+                on_event: move |ev| settings.write().overrides.slider_border = ev
+            }
+            OverrideColour{
+                name: "Slider track",
+                title: "Slider track: Replaces the slider track colour of generated maps",
+                value: overrides.slider_track,
+                on_event: move |ev| settings.write().overrides.slider_track = ev
+            }
+        }
+        div{
+            class: "option-container",
+            title: "Combo colours: Replaces the combo colours of generated maps. The original combo colours are kept if none are added",
+            "Combo colours "
+            overrides.combo_colours.iter().enumerate().map(|(ind, colour)| rsx!{
+                input {
+                    key: "{ind}",
+                    r#type: "color",
+                    value: "{colour}",
+                    onchange: move |ev|{
+                        if let Ok(colour) = ev.value.parse::<Colour>(){
+                            settings.write().overrides.combo_colours[ind] = colour;
+                        }
+                    }
+                }
+            })
+            if overrides.combo_colours.len() < 8{
+                rsx!{
+                    button {
+                        onclick: move |_| settings.write().overrides.combo_colours.push(Colour(255, 255, 255)),
+                        "+"
+                    }
+                }
+            }
+            if !overrides.combo_colours.is_empty(){
+                rsx!{
+                    button {
+                        onclick: move |_| { settings.write().overrides.combo_colours.pop(); },
+                        "-"
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Selector that keeps, enables or disables a boolean value of generated maps
+#[inline_props]
+fn OverrideToggle<'a>(cx: Scope, name: &'a str, title: &'a str, value: Option<bool>, on_event: EventHandler<'a, Option<bool>>) -> Element{
+    cx.render(rsx!{
+        div{
+            class: "option-container",
+            title: "{title}",
+            "{name} "
+            select {
+                class: "theme-selector",
+                value: match value{
+                    None => "Keep",
+                    Some(true) => "On",
+                    Some(false) => "Off"
+                },
+                onchange: move |ev| on_event.call(match ev.value.as_str(){
+                    "On" => Some(true),
+                    "Off" => Some(false),
+                    _ => None
+                }),
+                option { "Keep" }
+                option { "On" }
+                option { "Off" }
+            }
+        }
+    })
+}
+
+/// Colour picker for a colour of generated maps that is only replaced while its checkbox is checked
+#[inline_props]
+fn OverrideColour<'a>(cx: Scope, name: &'a str, title: &'a str, value: Option<Colour>, on_event: EventHandler<'a, Option<Colour>>) -> Element{
+    cx.render(rsx!{
+        div{
+            class: "option-container",
+            title: "{title}",
+            "{name} "
+            input {
+                r#type: "checkbox",
+                checked: "{value.is_some()}",
+                onclick: move |_| on_event.call(match value{
+                    Some(_) => None,
+                    None => Some(Colour(255, 255, 255))
+                })
+            }
+            if let Some(colour) = value{
+                rsx!{
+                    input {
+                        r#type: "color",
+                        value: "{colour}",
+                        onchange: move |ev|{
+                            if let Ok(colour) = ev.value.parse::<Colour>(){
+                                on_event.call(Some(colour));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Selector for the mode an osu!standard map is converted to
+fn ConvertOption(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let settings = use_shared_state::<Settings>(cx)?;
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Convert to: Converts the map to another mode the same way osu! does when playing converted maps",
+                "Convert to "
+                select {
+                    class: "theme-selector",
+                    value: match map.read().target_mode(){
+                        Mode::Osu => "osu!",
+                        Mode::Taiko => "Taiko",
+                        Mode::Catch => "Catch",
+                        Mode::Mania => "Mania"
+                    },
+                    onchange: move |ev|{
+                        let mut map = map.write();
+                        map.convert_mode = match ev.data.value.as_str(){
+                            "Taiko" => Some(Mode::Taiko),
+                            "Catch" => Some(Mode::Catch),
+                            "Mania" => Some(Mode::Mania),
+                            _ => None
+                        };
+                        // AR and OD scale differently in each mode
+                        map.apply_mods(&settings.read());
+                    },
+                    option { "osu!" }
+                    option { "Taiko" }
+                    option { "Catch" }
+                    option { "Mania" }
+                }
+            }
+        }
+    })
+}
+
+/// Difficulty of the original and the generated map over time with the breaks and the preview point marked.
+/// Clicking a column sets the practice range to it, and clicking a second column extends the range to that column.
+#[inline_props]
+fn StrainGraphComponent(cx: Scope, graph: StrainGraph) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let anchor = use_state(cx, || None::<usize>);
+    let max_strain = graph.max_strain().max(f64::EPSILON);
+    let points = |strains: &Vec<f64>| strains.iter()
+        .enumerate()
+        .map(|(column, strain)| format!("{},{}", column as f64 + 0.5, 100.0 - strain / max_strain * 95.0))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let (original_points, modified_points) = (points(&graph.original), points(&graph.modified));
+    let preview = graph.position(graph.preview_time);
+    let practice_range = map.read().practice_range;
+
+    cx.render(rsx!{
+        div{
+            class: "strain-graph-container",
+            title: "Strain graph: The difficulty of the original map (gray) and the generated map over time. Breaks are shaded and the preview point is marked. Click the graph to choose a practice range",
+            svg{
+                class: "strain-graph",
+                view_box: "0 0 {GRAPH_COLUMNS} 100",
+                preserve_aspect_ratio: "none",
+                graph.breaks.iter().map(|(start, end)|{
+                    let x = graph.position(*start);
+                    let width = graph.position(*end) - x;
+                    rsx!{ rect{ key: "{start}", class: "strain-break", x: "{x}", y: "0", width: "{width}", height: "100" } }
+                })
+                if let Some((start, end)) = practice_range{
+                    let x = graph.position(start as f64);
+                    let width = graph.position(end as f64) - x;
+                    rsx!{ rect{ class: "strain-practice", x: "{x}", y: "0", width: "{width}", height: "100" } }
+                }
+                polyline{ class: "strain-original", points: "{original_points}" }
+                polyline{ class: "strain-modified", points: "{modified_points}" }
+                line{ class: "strain-preview", x1: "{preview}", x2: "{preview}", y1: "0", y2: "100" }
+                (0..GRAPH_COLUMNS).map(|column| rsx!{
+                    rect{
+                        key: "{column}",
+                        class: "strain-column",
+                        x: "{column}",
+                        y: "0",
+                        width: "1",
+                        height: "100",
+                        onclick: move |_|{
+                            let (first, last) = match *anchor.get(){
+                                Some(anchor) => (anchor.min(column), anchor.max(column)),
+                                None => (column, column)
+                            };
+                            let start = graph.column_range(first).0.floor() as i32;
+                            let end = graph.column_range(last).1.ceil() as i32;
+                            map.write().practice_range = Some((start, end));
+                            anchor.set(if anchor.get().is_some() { None } else { Some(column) });
+                        }
+                    }
+                })
+            }
+            div{
+                class: "practice-range",
+                if let Some((start, end)) = practice_range{
+                    rsx!{
+                        "Practice range {format_time(start)} - {format_time(end)} "
+                        button{
+                            title: "Clear: Keeps the whole map instead of the practice range",
+                            onclick: move |_|{
+                                map.write().practice_range = None;
+                                anchor.set(None);
+                            },
+                            "Clear"
+                        }
+                    }
+                }else{
+                    rsx!{ "Click the graph to choose a practice range" }
+                }
+            }
+        }
+    })
+}
+
+/// Difficulty values and statistics of the last generated map next to those of its original map
+#[inline_props]
+fn ComparisonView<'a>(cx: Scope, original: MapStats, generated: MapStats, on_close: EventHandler<'a, ()>) -> Element{
+    cx.render(rsx!{
+        div{
+            class: "comparison",
+            title: "Comparison: The difficulty of the generated map compared to the original map",
+            div{
+                class: "close-button",
+                title: "Close comparison",
+                onclick: move |_| on_close.call(()),
+                "X"
+            }
+            table{
+                tr{
+                    th{}
+                    th{ "Original" }
+                    th{ "Generated" }
+                }
+                original.rows(generated).into_iter().map(|(name, original, generated)|{
+                    let class = if original != generated { "changed" } else { "" };
+                    rsx!{
+                        tr{
+                            key: "{name}",
+                            class: "{class}",
+                            td{ "{name}" }
+                            td{ "{original}" }
+                            td{ "{generated}" }
+                        }
+                    }
+                })
+            }
+        }
+    })
+}
+
+/// Approach and hit window timings of the generated map as the player experiences them at the chosen rate
+fn TimingInfo(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let timing = map.read().timing();
+
+    cx.render(rsx!{
+        div{
+            class: "timing-grid",
+            if let (Some(preempt), Some(ar)) = (timing.preempt, timing.effective_ar()){
+                rsx!{
+                    div{
+                        class: "option-container",
+                        title: "Approach: The time objects are visible before they have to be hit at the chosen rate, {round_dec(preempt, 2)}ms in the map file",
+                        "Approach {timing.effective(preempt)}ms (AR {ar})"
+                    }
+                }
+            }
+            if let Some(fade_in) = timing.fade_in{
+                rsx!{
+                    div{
+                        class: "option-container",
+                        title: "Fade in: The time objects take to fade in at the chosen rate, {round_dec(fade_in, 2)}ms in the map file",
+                        "Fade in {timing.effective(fade_in)}ms"
+                    }
+                }
+            }
+            if let Some(od) = timing.effective_od(){
+                rsx!{
+                    div{
+                        class: "option-container",
+                        title: "Hit windows: The time before or after an object in which each judgement is given at the chosen rate",
+                        timing.hit_windows.iter().map(|(name, window)| rsx!{
+                            span{ key: "{name}", "{name}: ±{timing.effective(*window)}ms " }
+                        })
+                        "(OD {od})"
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Finds the rate at which the generated map has the entered star rating
+fn StarTarget(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let settings = use_shared_state::<Settings>(cx)?;
+    let msg = use_shared_state::<StatusMessage>(cx)?;
+    let target = use_state(cx, || 5.0);
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Target stars: Changes the rate until the generated map has this star rating. AR and OD follow the rate if Scale AR and Scale OD are enabled",
+                "Target stars "
+                input {
+                    r#type: "number",
+                    min: 0,
+                    step: 0.1,
+                    value: "{target}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<f64>().unwrap_or(*target.get());
+                        target.set(round_dec(temp_val.max(0.0), 2));
+                    },
+                }
+                button {
+                    title: "Find rate: Searches for the rate at which the generated map has the target star rating",
+                    onclick: move |_|{
+                        let mut options = map.read().clone();
+                        match options.solve_rate_for_stars(&settings.read(), *target.get()){
+                            Ok(stars) => {
+                                msg.write().text = Some(format!("The map has {} stars at {}x", stars, options.rate));
+                                msg.write().status = Status::Success;
+                                *map.write() = options;
+                            },
+                            Err(e) => {
+                                msg.write().text = Some(format!("Error finding rate: {}", e));
+                                msg.write().status = Status::Error;
+                            }
+                        }
+                    },
+                    "Find rate"
+                }
+            }
+        }
+    })
+}
+
+/// Generates a practice map for each step of a range of rates, ARs or ODs into one mapset
+fn LadderComponent(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let settings = use_shared_state::<Settings>(cx)?;
+    let msg = use_shared_state::<StatusMessage>(cx)?;
+    let ladder = use_state(cx, Ladder::default);
+    let generating = use_state(cx, || false);
+    // Name, rate, AR, OD and star rating of each step of the last generated ladder
+    let steps = use_state(cx, Vec::<(String, f64, f64, f64, f64)>::new);
+    let set_number = |value: &str, current: f64| value.parse::<f64>().ok().filter(|x| x.is_finite()).map_or(current, |x| round_dec(x, 2));
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Ladder: Creates a map for each step from the first to the last value, all in the same mapset. The steps are named so they sort in order",
+                "Ladder "
+                select {
+                    class: "theme-selector",
+                    value: match ladder.value{
+                        LadderValue::Rate => "Rate",
+                        LadderValue::ApproachRate => "AR",
+                        LadderValue::OverallDifficulty => "OD",
+                    },
+                    onchange: move |ev|{
+                        let value = match ev.data.value.as_str(){
+                            "AR" => LadderValue::ApproachRate,
+                            "OD" => LadderValue::OverallDifficulty,
+                            _ => LadderValue::Rate
+                        };
+                        // Start from the current value of the map so the ladder only needs an end
+                        let start = match value{
+                            LadderValue::Rate => map.read().rate,
+                            LadderValue::ApproachRate => map.read().approach_rate,
+                            LadderValue::OverallDifficulty => map.read().overall_difficulty,
+                        };
+                        ladder.set(Ladder{ value, start, end: start, step: value.default_step() });
+                    },
+                    option { "Rate" }
+                    option { "AR" }
+                    option { "OD" }
+                }
+            }
+            div{
+                class: "option-container",
+                title: "From: The value of the first step",
+                "From "
+                input {
+                    r#type: "number",
+                    step: ladder.step,
+                    value: "{ladder.start}",
+                    onchange: move |ev| ladder.set(Ladder{ start: set_number(&ev.data.value, ladder.start), ..*ladder.get() }),
+                }
+            }
+            div{
+                class: "option-container",
+                title: "To: The value of the last step",
+                "To "
+                input {
+                    r#type: "number",
+                    step: ladder.step,
+                    value: "{ladder.end}",
+                    onchange: move |ev| ladder.set(Ladder{ end: set_number(&ev.data.value, ladder.end), ..*ladder.get() }),
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Step: The difference between the values of two steps",
+                "Step "
+                input {
+                    r#type: "number",
+                    min: 0.01,
+                    step: 0.01,
+                    value: "{ladder.step}",
+                    onchange: move |ev| ladder.set(Ladder{ step: set_number(&ev.data.value, ladder.step), ..*ladder.get() }),
+                }
+            }
+            button {
+                title: "Create ladder: Creates the map of every step. The audio is only decoded once and every rate only gets one audio file",
+                onclick: move |_| if !*generating.get(){
+                    let ladder_steps = match ladder.steps(&map.read(), &settings.read()){
+                        Ok(k) => k,
+                        Err(e) => {
+                            msg.write().text = Some(format!("Error creating ladder: {}", e));
+                            msg.write().status = Status::Error;
+                            return;
+                        }
+                    };
+                    steps.set(ladder_steps.iter().map(|x| (
+                        x.ladder_name().unwrap_or_default(),
+                        x.rate,
+                        x.approach_rate,
+                        x.overall_difficulty,
+                        x.performance(&settings.read()).map_or(0.0, |x| x.stars),
+                    )).collect());
+                    cx.spawn({
+                        generating.set(true);
+                        msg.write().text = Some(format!("Please wait, generating {} maps...", ladder_steps.len()));
+                        msg.write().status = Status::Success;
+                        to_owned![settings, msg, generating];
+                        async move{
+                            tokio::time::sleep(Duration::from_millis(100)).await; // Wait so the message can be displayed
+                            match generate_ladder(&ladder_steps, &settings.read()).await{
+                                Ok(generated) => {
+                                    msg.write().text = Some(format!("Ladder of {} maps created successfully!", generated.len()));
+                                    msg.write().status = Status::Success;
+                                },
+                                Err(e) => {
+                                    msg.write().text = Some(format!("Error creating ladder: {}", e));
+                                    msg.write().status = Status::Error;
+                                }
+                            };
+                            generating.set(false);
+                        }
+                    })
+                },
+                "Create ladder"
+            }
+        }
+        if !steps.is_empty(){
+            rsx!{
+                div{
+                    class: "ladder",
+                    title: "Ladder: The steps of the last created ladder",
+                    div{
+                        class: "close-button",
+                        title: "Close ladder",
+                        onclick: move |_| steps.set(Vec::new()),
+                        "X"
+                    }
+                    table{
+                        tr{
+                            th{}
+                            th{ "Rate" }
+                            th{ "AR" }
+                            th{ "OD" }
+                            th{ "Stars" }
+                        }
+                        steps.iter().map(|(name, rate, ar, od, stars)| rsx!{
+                            tr{
+                                key: "{name}",
+                                td{ "{name}" }
+                                td{ "{rate}x" }
+                                td{ "{ar}" }
+                                td{ "{od}" }
+                                td{ "{stars}" }
+                            }
+                        })
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Accuracy and misses used for the custom pp value of the generated map
+fn PpOptions(cx: Scope) -> Element{
+    let settings = use_shared_state::<Settings>(cx)?;
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Accuracy: The accuracy used for the custom pp value of the generated map",
+                "Accuracy "
+                input {
+                    r#type: "number",
+                    min: 0,
+                    max: 100,
+                    step: 0.5,
+                    value: "{settings.read().pp_accuracy}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<f64>().unwrap_or(settings.read().pp_accuracy);
+                        settings.write().pp_accuracy = temp_val.max(0.0).min(100.0);
+                    },
+                }
+                "%"
+            }
+            div{
+                class: "option-container",
+                title: "Misses: The amount of misses used for the custom pp value of the generated map",
+                "Misses "
+                input {
+                    r#type: "number",
+                    min: 0,
+                    step: 1,
+                    value: "{settings.read().pp_misses}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<u32>().unwrap_or(settings.read().pp_misses);
+                        settings.write().pp_misses = temp_val;
+                    },
+                }
+            }
+        }
+    })
+}
+
+/// Options for processing the background of generated maps
+fn BackgroundComponent(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let replacement = map.read().background_options.replacement.clone();
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Dim: Darkens the background of the generated map by the given percentage",
+                "Dim "
+                input {
+                    r#type: "number",
+                    min: 0,
+                    max: 100,
+                    step: 10,
+                    value: "{map.read().background_options.dim}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<u8>().unwrap_or(map.read().background_options.dim);
+                        map.write().background_options.dim = temp_val.min(100);
+                    },
+                }
+                "%"
+            }
+            div{
+                class: "option-container",
+                title: "Blur: Blurs the background of the generated map, higher values blur more",
+                "Blur "
+                input {
+                    r#type: "number",
+                    min: 0,
+                    max: 50,
+                    step: 1,
+                    value: "{map.read().background_options.blur}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<f32>().unwrap_or(map.read().background_options.blur);
+                        map.write().background_options.blur = temp_val.max(0.0).min(50.0);
+                    },
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Background: Replaces the background of the generated map with a solid colour or another image",
+                "Background "
+                select {
+                    class: "theme-selector",
+                    value: match replacement{
+                        None => "Original",
+                        Some(BackgroundReplacement::Colour(_)) => "Colour",
+                        Some(BackgroundReplacement::Image(_)) => "Image"
+                    },
+                    onchange: move |ev|{
+                        let replacement = match ev.data.value.as_str(){
+                            "Colour" => Some(BackgroundReplacement::Colour(Colour(0, 0, 0))),
+                            "Image" => {
+                                let image_picker = FileDialog::new()
+                                    .add_filter("Image", &["jpg", "jpeg", "png"])
+                                    .set_title("Choose a background image");
+                                image_picker.pick_file().map(BackgroundReplacement::Image)
+                            },
+                            _ => None
+                        };
+                        map.write().background_options.replacement = replacement;
+                    },
+                    option { "Original" }
+                    option { "Colour" }
+                    option { "Image" }
+                }
+                if let Some(BackgroundReplacement::Colour(colour)) = replacement{
+                    rsx!{
+                        input {
+                            r#type: "color",
+                            value: "{colour}",
+                            onchange: move |ev|{
+                                if let Ok(colour) = ev.value.parse::<Colour>(){
+                                    map.write().background_options.replacement = Some(BackgroundReplacement::Colour(colour));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Downscale: Shrinks backgrounds that are taller than the given height to reduce loading times",
+                "Downscale "
+                select {
+                    class: "theme-selector",
+                    value: match map.read().background_options.max_height{
+                        Some(k) => format!("{}p", k),
+                        None => "Off".to_string()
+                    },
+                    onchange: move |ev|{
+                        map.write().background_options.max_height = ev.data.value.trim_end_matches('p').parse::<u32>().ok();
+                    },
+                    option { "Off" }
+                    option { "1080p" }
+                    option { "720p" }
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Remove storyboard: Removes the video and storyboard of the generated map. The storyboard shared by the whole mapset (.osb file) is left out of the .osz file, but osu! still shows it with the generated map",
+                "Remove storyboard "
+                input {
+                    r#type: "checkbox",
+                    checked: "{map.read().strip_storyboard}",
+                    onclick: move |_| {
+                        let temp = map.read().strip_storyboard;
+                        map.write().strip_storyboard = !temp;
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Options for the geometric transforms of osu!standard maps
+pub fn TransformsComponent(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Mirror: Mirrors the map horizontally, vertically or along both axes (diagonal)",
+                "Mirror "
+                select {
+                    class: "theme-selector",
+                    value: match map.read().mirror{
+                        Mirror::None => "None",
+                        Mirror::Horizontal => "Horizontal",
+                        Mirror::Vertical => "Vertical",
+                        Mirror::Diagonal => "Diagonal"
+                    },
+                    onchange: move |ev|{
+                        map.write().mirror = ev.data.value.parse::<Mirror>().unwrap_or_default();
+                    },
+                    option { "None" }
+                    option { "Horizontal" }
+                    option { "Vertical" }
+                    option { "Diagonal" }
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Rotation: Rotates the map clockwise around the center of the playfield by the given amount of degrees",
+                "Rotation "
+                input {
+                    r#type: "number",
+                    min: -360,
+                    max: 360,
+                    step: 15,
+                    value: "{map.read().rotation}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<f64>().unwrap_or(map.read().rotation);
+                        map.write().rotation = temp_val.max(-360.0).min(360.0);
+                    },
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Spacing: Multiplies the distance between hit objects by the given factor, keeping them inside the playfield",
+                "Spacing "
+                input {
+                    r#type: "number",
+                    min: 0,
+                    max: 5,
+                    step: 0.1,
+                    value: "{map.read().spacing}",
+                    onchange: move |ev|{
+                        let temp_val = ev.data.value.parse::<f64>().unwrap_or(map.read().spacing);
+                        map.write().spacing = round_dec(temp_val.max(0.0).min(5.0), 2);
+                    },
+                }
+            }
+            RandomOption{
+                title: "Random: Changes the angles between hit objects while keeping their distance and rhythm. The same seed always generates the same map, and it is saved in the difficulty name and tags of the generated map."
+            }
+        }
+    })
+}
+
+/// Options for the transforms that make a map easier by removing or simplifying hit objects
+pub fn SimplifyComponent(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let spinners = map.read().spinners;
+    let stream_thinning = map.read().stream_thinning;
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Sliders to circles: Converts every slider into a circle",
+                "Sliders to circles "
+                input {
+                    r#type: "checkbox",
+                    checked: "{map.read().sliders_to_circles}",
+                    onclick: move |_| {
+                        let temp = map.read().sliders_to_circles;
+                        map.write().sliders_to_circles = !temp;
+                    }
+                }
+            }
+            if map.read().sliders_to_circles{
+                rsx!{
+                    div{
+                        class: "option-container",
+                        title: "Tail circles: Adds a circle where each converted slider ends",
+                        "Tail circles "
+                        input {
+                            r#type: "checkbox",
+                            checked: "{map.read().slider_tail_circles}",
+                            onclick: move |_| {
+                                let temp = map.read().slider_tail_circles;
+                                map.write().slider_tail_circles = !temp;
+                            }
+                        }
+                    }
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Spinners: Keeps, removes or shortens the spinners of the map",
+                "Spinners "
+                select {
+                    class: "theme-selector",
+                    value: match spinners{
+                        Spinners::Keep => "Keep",
+                        Spinners::Remove => "Remove",
+                        Spinners::Shorten(_) => "Shorten"
+                    },
+                    onchange: move |ev|{
+                        map.write().spinners = match ev.data.value.as_str(){
+                            "Remove" => Spinners::Remove,
+                            "Shorten" => Spinners::Shorten(1000),
+                            _ => Spinners::Keep
+                        };
+                    },
+                    option { "Keep" }
+                    option { "Remove" }
+                    option { "Shorten" }
+                }
+                if let Spinners::Shorten(max_length) = spinners{
+                    rsx!{
+                        input {
+                            r#type: "number",
+                            title: "Maximum spinner length in milliseconds",
+                            min: 0,
+                            step: 100,
+                            value: "{max_length}",
+                            onchange: move |ev|{
+                                if let Ok(max_length) = ev.data.value.parse::<u32>(){
+                                    map.write().spinners = Spinners::Shorten(max_length);
+                                }
+                            },
+                        }
+                    }
+                }
+            }
+            div{
+                class: "option-container",
+                title: "Thin streams: Removes every other note from streams that are snapped to the chosen beat divisor or faster",
+                "Thin streams "
+                select {
+                    class: "theme-selector",
+                    value: match stream_thinning{
+                        Some(divisor) => format!("1/{}", divisor),
+                        None => "Off".to_string()
+                    },
+                    onchange: move |ev|{
+                        map.write().stream_thinning = ev.data.value.strip_prefix("1/").and_then(|x| x.parse::<u32>().ok());
+                    },
+                    option { "Off" }
+                    option { "1/2" }
+                    option { "1/3" }
+                    option { "1/4" }
+                    option { "1/6" }
+                    option { "1/8" }
+                }
+            }
+        }
+    })
+}
+
+/// Checkbox and seed input for the randomizing transforms
+#[inline_props]
+fn RandomOption<'a>(cx: Scope, title: &'a str) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+
+    cx.render(rsx!{
+        div{
+            class: "option-container",
+            title: "{title}",
+            "Random "
+            input {
+                r#type: "checkbox",
+                checked: "{map.read().random_seed.is_some()}",
+                onclick: move |_| {
+                    let temp = map.read().random_seed;
+                    map.write().random_seed = match temp{
+                        Some(_) => None,
+                        None => Some(new_seed())
+                    };
+                }
+            }
+            if let Some(seed) = map.read().random_seed{
+                rsx!{
+                    input {
+                        r#type: "number",
+                        min: 0,
+                        step: 1,
+                        value: "{seed}",
+                        onchange: move |ev|{
+                            if let Ok(seed) = ev.data.value.parse::<u64>(){
+                                map.write().random_seed = Some(seed);
+                            }
+                        },
+                    }
+                    button {
+                        title: "New seed: Generates a new random seed",
+                        onclick: move |_| map.write().random_seed = Some(new_seed()),
+                        "New seed"
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// Options for the column transforms of osu!mania maps
+pub fn ManiaComponent(cx: Scope) -> Element{
+    let map = use_shared_state::<MapOptions>(cx)?;
+    let long_notes = map.read().long_notes;
+
+    cx.render(rsx!{
+        div{
+            class: "transform-grid",
+            div{
+                class: "option-container",
+                title: "Mirror: Mirrors the columns of the map",
+                "Mirror "
+                input {
+                    r#type: "checkbox",
+                    checked: "{map.read().mirror == Mirror::Horizontal}",
+                    onclick: move |_| {
+                        let temp = map.read().mirror;
+                        map.write().mirror = match temp{
+                            Mirror::Horizontal => Mirror::None,
+                            _ => Mirror::Horizontal
+                        };
+                    }
+                }
+            }
+            RandomOption{
+                title: "Random: Shuffles the columns of the map. The same seed always generates the same map, and it is saved in the difficulty name and tags of the generated map."
+            }
+            div{
+                class: "option-container",
+                title: "Long notes: Turns every note into a long note (Full LN), every long note into a regular note (No LN), or the gaps between notes into long notes and vice versa (Invert)",
+                "Long notes "
+                select {
+                    class: "theme-selector",
+                    value: match long_notes{
+                        LongNotes::Keep => "Keep",
+                        LongNotes::Full => "Full LN",
+                        LongNotes::Remove => "No LN",
+                        LongNotes::Invert => "Invert"
+                    },
+                    onchange: move |ev|{
+                        map.write().long_notes = match ev.data.value.as_str(){
+                            "Full LN" => LongNotes::Full,
+                            "No LN" => LongNotes::Remove,
+                            "Invert" => LongNotes::Invert,
+                            _ => LongNotes::Keep
+                        };
+                    },
+                    option { "Keep" }
+                    option { "Full LN" }
+                    option { "No LN" }
+                    option { "Invert" }
+                }
+            }
+            if let LongNotes::Full | LongNotes::Invert = long_notes{
+                rsx!{
+                    div{
+                        class: "option-container",
+                        title: "Gap: The gap between the end of a long note and the next note in its column, in beats",
+                        "Gap "
+                        select {
+                            class: "theme-selector",
+                            value: "1/{map.read().ln_gap}",
+                            onchange: move |ev|{
+                                if let Some(divisor) = ev.data.value.strip_prefix("1/").and_then(|x| x.parse::<u32>().ok()){
+                                    map.write().ln_gap = divisor;
+                                }
+                            },
+                            option { "1/1" }
+                            option { "1/2" }
+                            option { "1/4" }
+                            option { "1/8" }
+                            option { "1/16" }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
 fn LockedLock(cx: Scope) -> Element{
     cx.render(rsx!{
         svg {
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use rosu_pp::{BeatmapExt, DifficultyAttributes};
+use serde_json::{json, Value};
+use crate::{structs::{game_mode, mode_name, MapOptions, Performance, Settings, Severity, ValidationIssue}, utils::{output_paths, round_dec}};
+
+/// Machine readable description of a map and of the map that would be generated from it with the options.
+/// The map needs to be loaded with read_map_metadata first.
+pub fn map_json(map: &MapOptions, settings: &Settings) -> Result<Value>{
+    let loaded = map.loaded_map(settings)?;
+    let rate = map.effective_rate();
+    let original = loaded.difficulty.stars().calculate();
+    let modified = map.difficulty_beatmap(settings)?.stars()
+        .mode(game_mode(map.target_mode()))
+        .clock_rate(rate)
+        .calculate();
+    let performance = map.performance(settings)?;
+    let outputs = output_paths(map, settings, &loaded.beatmap.audio_filename);
+    let (errors, warnings): (Vec<ValidationIssue>, Vec<ValidationIssue>) = map.validate(settings).into_iter()
+        .partition(|x| x.severity == Severity::Error);
+    let messages = |issues: &[ValidationIssue]| issues.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+
+    Ok(json!({
+        "path": settings.songs_path.join(&map.map_path),
+        "title": map.title,
+        "artist": map.artist,
+        "difficulty_name": map.difficulty_name,
+        "mode": mode_name(map.mode),
+        "options": {
+            "mode": mode_name(map.target_mode()),
+            "rate": map.rate,
+            "mods": map.mods.to_string(),
+            "effective_rate": rate,
+            "approach_rate": map.approach_rate,
+            "circle_size": map.circle_size,
+            "hp_drain": map.hp_drain,
+            "overall_difficulty": map.overall_difficulty,
+            "slider_multiplier": map.slider_multiplier,
+            "slider_tick_rate": map.slider_tick_rate,
+            "stack_leniency": map.stack_leniency,
+            "variant": map.variant_name(),
+        },
+        "bpm": {
+            "original": { "dominant": map.bpm_info.dominant, "min": map.bpm_info.min, "max": map.bpm_info.max },
+            "modified": { "dominant": round_dec(map.bpm_info.dominant * rate, 2), "min": round_dec(map.bpm_info.min * rate, 2), "max": round_dec(map.bpm_info.max * rate, 2) },
+        },
+        "original": attributes_json(&original),
+        "modified": attributes_json(&modified),
+        // The modified attributes and pp only account for the difficulty values if the objects are changed
+        "difficulty_values_only": map.changes_objects(),
+        "pp": {
+            "accuracies": Performance::ACCURACIES.iter().zip(performance.pp).map(|(accuracy, pp)| json!({ "accuracy": accuracy, "pp": pp })).collect::<Vec<Value>>(),
+            "custom": { "accuracy": settings.pp_accuracy, "misses": settings.pp_misses, "pp": performance.custom_pp },
+        },
+        "output": {
+            "map": outputs.map,
+            "audio": outputs.audio,
+            "background": outputs.background,
+            "osz": outputs.osz,
+        },
+        "warnings": messages(&warnings),
+        "errors": messages(&errors),
+    }))
+}
+
+/// The difficulty attributes calculated by rosu-pp for the mode of the map.
+fn attributes_json(attributes: &DifficultyAttributes) -> Value{
+    match attributes{
+        DifficultyAttributes::Osu(x) => json!({
+            "stars": x.stars,
+            "aim": x.aim,
+            "speed": x.speed,
+            "flashlight": x.flashlight,
+            "slider_factor": x.slider_factor,
+            "speed_note_count": x.speed_note_count,
+            "approach_rate": x.ar,
+            "overall_difficulty": x.od,
+            "hp_drain": x.hp,
+            "circles": x.n_circles,
+            "sliders": x.n_sliders,
+            "spinners": x.n_spinners,
+            "max_combo": x.max_combo,
+        }),
+        DifficultyAttributes::Taiko(x) => json!({
+            "stars": x.stars,
+            "stamina": x.stamina,
+            "rhythm": x.rhythm,
+            "colour": x.colour,
+            "peak": x.peak,
+            "hit_window": x.hit_window,
+            "max_combo": x.max_combo,
+        }),
+        DifficultyAttributes::Catch(x) => json!({
+            "stars": x.stars,
+            "approach_rate": x.ar,
+            "fruits": x.n_fruits,
+            "droplets": x.n_droplets,
+            "tiny_droplets": x.n_tiny_droplets,
+        }),
+        DifficultyAttributes::Mania(x) => json!({
+            "stars": x.stars,
+            "hit_window": x.hit_window,
+            "max_combo": x.max_combo,
+        }),
+    }
+}
@@ -0,0 +1,243 @@
+use std::{collections::HashMap, fmt, path::Path};
+use anyhow::Result;
+use crate::structs::LoadedMap;
+
+/// Sections of .osu files that consist of `key: value` pairs
+const KEY_VALUE_SECTIONS: [&str; 5] = ["General", "Editor", "Metadata", "Difficulty", "Colours"];
+/// Keys that ruso always writes with new values, so their values are not compared
+const CHANGED_KEYS: [(&str, &str); 12] = [
+    ("General", "AudioFilename"), ("General", "PreviewTime"), ("General", "StackLeniency"), ("General", "Mode"),
+    ("Metadata", "Version"), ("Metadata", "Tags"),
+    ("Difficulty", "HPDrainRate"), ("Difficulty", "CircleSize"), ("Difficulty", "OverallDifficulty"),
+    ("Difficulty", "ApproachRate"), ("Difficulty", "SliderMultiplier"), ("Difficulty", "SliderTickRate"),
+];
+
+/// Content of a source .osu file that is missing from its re-serialized version
+#[derive(Clone, Debug, PartialEq)]
+pub enum Loss{
+    /// A whole section is missing
+    Section(String),
+    /// A key of a `key: value` section is missing
+    Key{ section: String, key: String },
+    /// A key of a `key: value` section is written with a different value
+    Value{ section: String, key: String, source: String, output: String },
+    /// Lines of a list section such as [Events] are missing
+    Lines{ section: String, count: usize },
+    /// The file is written with a different format version
+    FormatVersion{ source: u32, output: u32 },
+}
+
+impl Loss{
+    /// Whether content of the map is lost. Other losses only mean that the map is written differently,
+    /// so they are reported as warnings and do not prevent the map from being generated.
+    /// Lost events are only expected if the storyboard is stripped from the map.
+    pub fn is_content(&self, strip_storyboard: bool) -> bool{
+        match self{
+            Loss::Section(_) | Loss::Key{ .. } | Loss::Value{ .. } => true,
+            Loss::Lines{ section, .. } => section != "Events" || !strip_storyboard,
+            Loss::FormatVersion{ .. } => false,
+        }
+    }
+
+    /// Whether the loss is removed from the map on purpose when the storyboard is stripped.
+    pub fn is_storyboard(&self) -> bool{
+        matches!(self, Loss::Lines{ section, .. } if section == "Events")
+    }
+}
+
+impl fmt::Display for Loss{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            Loss::Section(section) => write!(f, "The [{}] section would be lost", section),
+            Loss::Key{ section, key } => write!(f, "'{}' in [{}] would be lost", key, section),
+            Loss::Value{ section, key, source, output } => write!(f, "'{}' in [{}] would be written as '{}' instead of '{}'", key, section, output, source),
+            Loss::Lines{ section, count } => write!(f, "{} line(s) of [{}] would be lost", count, section),
+            Loss::FormatVersion{ source, output } => write!(f, "The map would be written as format v{} instead of v{}", output, source),
+        }
+    }
+}
+
+/// Finds the content of `source` that is missing from `output` or written with a different value.
+/// Values are compared without whitespace and as numbers if they are numbers, so only the formatting may differ.
+/// Hit objects, timing points and the keys in CHANGED_KEYS are not compared since ruso changes them on purpose.
+pub fn find_losses(source: &str, output: &str) -> Vec<Loss>{
+    let mut losses = Vec::new();
+    if let (Some(source), Some(output)) = (format_version(source), format_version(output)){
+        if source != output{
+            losses.push(Loss::FormatVersion{ source, output });
+        }
+    }
+
+    let output_sections = sections(output);
+    for (name, lines) in sections(source){
+        let Some((_, output_lines)) = output_sections.iter().find(|(x, _)| *x == name) else {
+            if !lines.is_empty(){
+                losses.push(Loss::Section(name.to_string()));
+            }
+            continue;
+        };
+        if KEY_VALUE_SECTIONS.contains(&name){
+            let output_values = output_lines.iter().filter_map(|x| key_value(x)).collect::<HashMap<&str, &str>>();
+            for (key, value) in lines.iter().filter_map(|x| key_value(x)){
+                match output_values.get(key){
+                    None => losses.push(Loss::Key{ section: name.to_string(), key: key.to_string() }),
+                    Some(output) if !same_value(value, output) && !CHANGED_KEYS.contains(&(name, key)) => losses.push(Loss::Value{
+                        section: name.to_string(),
+                        key: key.to_string(),
+                        source: value.to_string(),
+                        output: output.to_string(),
+                    }),
+                    Some(_) => (),
+                }
+            }
+        }else if name == "Events" && output_lines.len() < lines.len(){
+            losses.push(Loss::Lines{ section: name.to_string(), count: lines.len() - output_lines.len() });
+        }
+    }
+    losses
+}
+
+/// Loads the map at the given path the same way it is loaded for generation and returns the content that would be lost.
+pub fn verify_map(path: &Path) -> Result<Vec<Loss>>{
+    Ok(LoadedMap::load(path)?.losses)
+}
+
+/// Splits a .osu file into its sections, skipping empty lines and comments.
+fn sections(contents: &str) -> Vec<(&str, Vec<&str>)>{
+    let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in contents.lines().skip(1){
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("//"){
+            continue;
+        }
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|x| x.strip_suffix(']')){
+            sections.push((name, Vec::new()));
+        }else if let Some((_, lines)) = sections.last_mut(){
+            lines.push(line);
+        }
+    }
+    sections
+}
+
+/// Key and value of a `key: value` line
+fn key_value(line: &str) -> Option<(&str, &str)>{
+    line.split_once(':').map(|(key, value)| (key.trim(), value.trim()))
+}
+
+/// Whether two values only differ in their formatting.
+fn same_value(source: &str, output: &str) -> bool{
+    // Lists such as colours may be written with or without spaces after their commas
+    let normalize = |x: &str| x.split(',').map(str::trim).collect::<Vec<&str>>().join(",");
+    let (source, output) = (normalize(source), normalize(output));
+    source == output || matches!((source.parse::<f64>(), output.parse::<f64>()), (Ok(a), Ok(b)) if a == b)
+}
+
+/// Version from the `osu file format vX` header
+fn format_version(contents: &str) -> Option<u32>{
+    contents.lines().next()?
+        .trim_start_matches('\u{feff}').trim()
+        .strip_prefix("osu file format v")?
+        .parse().ok()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const SOURCE: &str = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+Mode: 0
+
+[Metadata]
+Title:Song
+Version:Insane
+
+[Events]
+//Background and Video events
+0,0,\"bg.jpg\",0,0
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,1000,2000,1,0
+
+[HitObjects]
+256,192,1000,1,0,0:0:0:0:
+";
+
+    #[test]
+    fn identical_maps_lose_nothing(){
+        assert_eq!(find_losses(SOURCE, SOURCE), Vec::new());
+    }
+
+    #[test]
+    fn missing_sections_and_keys_are_content_losses(){
+        let output = "osu file format v14
+
+[General]
+Mode: 0
+
+[Events]
+0,0,\"bg.jpg\",0,0
+Sprite,Foreground,Centre,\"sb/star.png\",320,240
+ F,0,1000,2000,1,0
+
+[HitObjects]
+256,192,1000,1,0,0:0:0:0:
+";
+        let losses = find_losses(SOURCE, output);
+        assert_eq!(losses, vec![
+            Loss::Key{ section: "General".to_string(), key: "AudioFilename".to_string() },
+            Loss::Section("Metadata".to_string()),
+        ]);
+        assert!(losses.iter().all(|x| x.is_content(true)));
+    }
+
+    #[test]
+    fn changed_values_are_content_losses(){
+        let source = format!("{}\n[Editor]\nBookmarks: 1000,2000\nDistanceSpacing: 1.0\n", SOURCE);
+        let output = format!("{}\n[Editor]\nBookmarks:\nDistanceSpacing: 1\n", SOURCE);
+        let losses = find_losses(&source, &output);
+        assert_eq!(losses, vec![Loss::Value{
+            section: "Editor".to_string(),
+            key: "Bookmarks".to_string(),
+            source: "1000,2000".to_string(),
+            output: "".to_string(),
+        }]);
+        assert!(losses[0].is_content(false));
+    }
+
+    #[test]
+    fn values_changed_by_ruso_are_not_compared(){
+        let output = SOURCE.replace("Version:Insane", "Version:Insane 1.5x").replace("audio.mp3", "audio 1.5x.mp3");
+        assert_eq!(find_losses(SOURCE, &output), Vec::new());
+    }
+
+    #[test]
+    fn lost_events_are_content_unless_the_storyboard_is_stripped(){
+        let output = SOURCE.replace("Sprite,Foreground,Centre,\"sb/star.png\",320,240\n F,0,1000,2000,1,0\n", "");
+        let losses = find_losses(SOURCE, &output);
+        assert_eq!(losses, vec![Loss::Lines{ section: "Events".to_string(), count: 2 }]);
+        assert!(losses[0].is_content(false));
+        assert!(!losses[0].is_content(true));
+        assert!(losses[0].is_storyboard());
+    }
+
+    #[test]
+    fn format_version_is_a_warning(){
+        let losses = find_losses(SOURCE, &SOURCE.replace("v14", "v12"));
+        assert_eq!(losses, vec![Loss::FormatVersion{ source: 14, output: 12 }]);
+        assert!(!losses[0].is_content(false));
+    }
+
+    #[test]
+    fn values_written_differently_are_not_losses(){
+        let output = SOURCE.replace("Mode: 0", "Mode:0").replace("Title:Song", "Title: Song");
+        assert_eq!(find_losses(SOURCE, &output), Vec::new());
+    }
+
+    #[test]
+    fn empty_sections_are_not_losses(){
+        let source = format!("{}\n[Colours]\n", SOURCE);
+        assert_eq!(find_losses(&source, SOURCE), Vec::new());
+    }
+}
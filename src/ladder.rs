@@ -0,0 +1,195 @@
+use std::{fmt, str::FromStr};
+use anyhow::{Result, anyhow};
+use crate::{structs::{MapOptions, Settings}, utils::round_dec};
+
+/// Most steps a ladder can have, which keeps the mapset and the time it takes to generate it reasonable
+pub const MAX_LADDER_STEPS: usize = 50;
+
+/// The value that changes between the steps of a training ladder
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LadderValue{
+    #[default]
+    Rate,
+    ApproachRate,
+    OverallDifficulty,
+}
+
+impl LadderValue{
+    /// Step used if the ladder does not give one.
+    pub fn default_step(&self) -> f64{
+        match self{
+            LadderValue::Rate => 0.05,
+            LadderValue::ApproachRate | LadderValue::OverallDifficulty => 0.5,
+        }
+    }
+}
+
+impl fmt::Display for LadderValue{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        write!(f, "{}", match self{
+            LadderValue::Rate => "rate",
+            LadderValue::ApproachRate => "ar",
+            LadderValue::OverallDifficulty => "od",
+        })
+    }
+}
+
+impl FromStr for LadderValue{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self>{
+        match s.to_lowercase().as_str(){
+            "rate" => Ok(LadderValue::Rate),
+            "ar" => Ok(LadderValue::ApproachRate),
+            "od" => Ok(LadderValue::OverallDifficulty),
+            _ => Err(anyhow!("Invalid ladder value: '{}'. Valid values are 'rate', 'ar' and 'od'.", s))
+        }
+    }
+}
+
+/// A range of values that a practice map is generated for each step of, going from the start to the end
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ladder{
+    pub value: LadderValue,
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+impl Default for Ladder{
+    fn default() -> Self{
+        Self{ value: LadderValue::Rate, start: 1.0, end: 1.3, step: 0.05 }
+    }
+}
+
+impl Ladder{
+    /// The value of each step. The end is always the last step, even if it is less than a step away from the one before it.
+    pub fn values(&self) -> Result<Vec<f64>>{
+        if !self.step.is_finite() || self.step <= 0.0{
+            return Err(anyhow!("The step of a ladder must be a positive number, not {}.", self.step));
+        }
+        if !self.start.is_finite() || !self.end.is_finite(){
+            return Err(anyhow!("The range of a ladder must be two numbers, not {} and {}.", self.start, self.end));
+        }
+        // The small offset keeps rounding errors from dropping the end of ranges that are a multiple of the step
+        let steps = ((self.end - self.start).abs() / self.step + 1e-9) as usize;
+        if steps + 1 > MAX_LADDER_STEPS{
+            return Err(anyhow!("A ladder from {} to {} in steps of {} has more than {} steps.", self.start, self.end, self.step, MAX_LADDER_STEPS));
+        }
+        let direction = if self.end < self.start { -1.0 } else { 1.0 };
+        let mut values = (0..=steps).map(|x| round_dec(self.start + direction * x as f64 * self.step, 2)).collect::<Vec<f64>>();
+        let end = round_dec(self.end, 2);
+        if values.last() != Some(&end){
+            values.push(end);
+        }
+        Ok(values)
+    }
+
+    /// Options of each step, which are the options of the map with the value of the ladder changed.
+    /// AR and OD are scaled with the rate of each step the same way they are for a single map.
+    pub fn steps(&self, map: &MapOptions, settings: &Settings) -> Result<Vec<MapOptions>>{
+        let values = self.values()?;
+        let count = values.len();
+        Ok(values.into_iter().enumerate().map(|(ind, value)|{
+            let mut step = map.clone();
+            match self.value{
+                LadderValue::Rate => step.set_rate(value, settings),
+                LadderValue::ApproachRate => step.approach_rate = value,
+                LadderValue::OverallDifficulty => step.overall_difficulty = value,
+            }
+            step.ladder_step = Some((ind + 1, count));
+            step
+        }).collect())
+    }
+}
+
+impl fmt::Display for Ladder{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        write!(f, "{}:{}-{}:{}", self.value, self.start, self.end, self.step)
+    }
+}
+
+impl FromStr for Ladder{
+    type Err = anyhow::Error;
+
+    /// Parses 'rate:1-1.3:0.05' as the rates from 1x to 1.3x in steps of 0.05.
+    /// The step can be left out to use the default step of the value, e.g. 'ar:8-10'.
+    fn from_str(s: &str) -> Result<Self>{
+        let invalid = || anyhow!("Invalid ladder: '{}'. Use a value, a range and an optional step such as 'rate:1-1.3:0.05' or 'ar:8-10:0.5'.", s);
+        let mut parts = s.trim().split(':');
+        let value = parts.next().ok_or_else(invalid)?.parse::<LadderValue>()?;
+        let (start, end) = parts.next().and_then(|x| x.split_once('-')).ok_or_else(invalid)?;
+        let step = match parts.next(){
+            Some(step) => step.trim().parse::<f64>().map_err(|_| invalid())?,
+            None => value.default_step(),
+        };
+        if parts.next().is_some(){
+            return Err(invalid());
+        }
+        let ladder = Ladder{
+            value,
+            start: start.trim().parse::<f64>().map_err(|_| invalid())?,
+            end: end.trim().parse::<f64>().map_err(|_| invalid())?,
+            step,
+        };
+        // Check the range now so that it does not only fail once the map is read
+        ladder.values()?;
+        Ok(ladder)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn values(ladder: &str) -> Vec<f64>{
+        ladder.parse::<Ladder>().unwrap().values().unwrap()
+    }
+
+    #[test]
+    fn values_include_both_ends(){
+        assert_eq!(values("rate:1-1.3:0.05"), vec![1.0, 1.05, 1.1, 1.15, 1.2, 1.25, 1.3]);
+        assert_eq!(values("od:8-8"), vec![8.0]);
+    }
+
+    #[test]
+    fn reverse_ranges_count_down(){
+        assert_eq!(values("ar:10-8"), vec![10.0, 9.5, 9.0, 8.5, 8.0]);
+        assert_eq!(values("rate:1.2-1:0.1"), vec![1.2, 1.1, 1.0]);
+    }
+
+    #[test]
+    fn end_is_kept_if_it_is_not_a_step_away(){
+        assert_eq!(values("rate:1-1.12:0.05"), vec![1.0, 1.05, 1.1, 1.12]);
+        assert_eq!(values("ar:10-8.8"), vec![10.0, 9.5, 9.0, 8.8]);
+    }
+
+    #[test]
+    fn ladders_are_limited_to_the_max_steps(){
+        assert_eq!(values("rate:1-3.45:0.05").len(), MAX_LADDER_STEPS);
+        assert!("rate:1-3.5:0.05".parse::<Ladder>().is_err());
+        let ladder = Ladder{ value: LadderValue::Rate, start: 1.0, end: 100.0, step: 0.05 };
+        assert!(ladder.values().is_err());
+    }
+
+    #[test]
+    fn parses_ladders(){
+        assert_eq!("rate:1-1.3:0.05".parse::<Ladder>().unwrap(), Ladder::default());
+        assert_eq!("AR:8-10".parse::<Ladder>().unwrap(), Ladder{ value: LadderValue::ApproachRate, start: 8.0, end: 10.0, step: 0.5 });
+        assert_eq!(" od:7 - 9:0.25 ".parse::<Ladder>().unwrap(), Ladder{ value: LadderValue::OverallDifficulty, start: 7.0, end: 9.0, step: 0.25 });
+    }
+
+    #[test]
+    fn rejects_invalid_ladders(){
+        for ladder in ["", "rate", "rate:1", "speed:1-2", "rate:a-b", "rate:1-1.3:0", "rate:1-1.3:-0.1", "rate:1-1.3:x", "rate:1-1.3:0.05:1", "rate:1-inf"]{
+            assert!(ladder.parse::<Ladder>().is_err(), "{}", ladder);
+        }
+    }
+
+    #[test]
+    fn ladders_display_as_they_are_parsed(){
+        for ladder in ["rate:1-1.3:0.05", "ar:10-8:0.5", "od:7-9:0.25"]{
+            assert_eq!(ladder.parse::<Ladder>().unwrap().to_string(), ladder);
+        }
+    }
+}
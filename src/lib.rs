@@ -5,13 +5,29 @@
 
 /// Audio manipulation functions
 pub mod audio;
+/// Background image processing functions
+pub mod background;
 /// CLI related functions
 pub mod cli;
+/// Comparison of generated maps with their original maps
+pub mod compare;
 /// Dioxus components
 pub mod components;
+/// JSON export of maps and the maps generated from them
+pub mod export;
+/// Checks for content lost when re-serializing maps
+pub mod fidelity;
+/// Training ladders of practice maps with increasing difficulty
+pub mod ladder;
 /// Props for Dioxus components
 pub mod props;
+/// Strains of maps over time for the strain graph
+pub mod strains;
 /// Settings and MapOptions structs
 pub mod structs;
+/// Approach and hit window timings
+pub mod timing;
+/// Hit object transformation functions
+pub mod transforms;
 /// General utility functions
 pub mod utils;
@@ -1,11 +1,27 @@
 use dioxus::prelude::*;
+use crate::timing::TimedValue;
 
 #[derive(Props)]
 pub struct SliderProps<'a>{
     pub name: &'a str,
     pub acronym: &'a str,
     pub read: f64,
+    #[props(default = 0.0)]
+    pub min: f64,
+    #[props(default = 10.0)]
+    pub max: f64,
+    #[props(default = 0.1)]
+    pub step: f64,
     pub locked: bool,
+    /// Grays out sliders of values that have no effect on the loaded map
+    #[props(default)]
+    pub disabled: bool,
+    /// Warnings and errors of the validation shown next to the slider
+    #[props(default)]
+    pub note: Option<String>,
+    /// Lets the value be entered as the player experiences it at the given rate
+    #[props(default)]
+    pub timed: Option<(TimedValue, f64)>,
     pub on_event: EventHandler<'a, f64>, 
     pub on_lock: EventHandler<'a, bool>, 
 }
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use rosu_pp::{BeatmapExt, Strains};
+use crate::structs::{game_mode, MapOptions, Settings};
+
+/// Amount of columns the strains of a map are grouped into for the graph
+pub const GRAPH_COLUMNS: usize = 100;
+
+/// Difficulty over time of the original and the generated map, grouped into columns of equal length.
+/// All times are in milliseconds of the original map so both maps line up.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StrainGraph{
+    /// Time of the end of the last hit object
+    pub length: f64,
+    /// Highest strain of each column of the original map
+    pub original: Vec<f64>,
+    /// Highest strain of each column of the generated map
+    pub modified: Vec<f64>,
+    /// Start and end times of the breaks
+    pub breaks: Vec<(f64, f64)>,
+    pub preview_time: f64,
+}
+
+impl StrainGraph{
+    /// Highest strain of both maps, used to scale the graph.
+    pub fn max_strain(&self) -> f64{
+        self.original.iter().chain(&self.modified).copied().fold(0.0, f64::max)
+    }
+
+    /// Start and end time of a column.
+    pub fn column_range(&self, column: usize) -> (f64, f64){
+        let width = self.length / GRAPH_COLUMNS as f64;
+        (column as f64 * width, (column + 1) as f64 * width)
+    }
+
+    /// Position of a time on the graph in columns.
+    pub fn position(&self, time: f64) -> f64{
+        if self.length > 0.0 { time / self.length * GRAPH_COLUMNS as f64 } else { 0.0 }
+    }
+}
+
+/// Calculates the strains of the original map and the generated map for the strain graph.
+pub fn strain_graph(map: &MapOptions, settings: &Settings) -> Result<StrainGraph>{
+    let loaded = map.loaded_map(settings)?;
+    let original = &loaded.difficulty;
+    let modified = map.difficulty_beatmap(settings)?;
+    let length = original.hit_objects.iter().map(|h| h.end_time()).fold(0.0, f64::max);
+    let first_object = original.hit_objects.first().map_or(0.0, |h| h.start_time);
+
+    let original_strains = original.stars().strains();
+    let modified_strains = modified.stars()
+        .mode(game_mode(map.target_mode()))
+        .clock_rate(map.effective_rate())
+        .strains();
+    Ok(StrainGraph{
+        length,
+        original: columns(&original_strains, first_object, length, 1.0),
+        modified: columns(&modified_strains, first_object, length, map.effective_rate()),
+        breaks: original.breaks.iter().map(|x| (x.start_time, x.end_time)).collect(),
+        preview_time: map.preview_time as f64,
+    })
+}
+
+/// Groups the strains of each section into the columns of the graph, keeping the highest strain of each column.
+/// Strains calculated at a rate are in the time of the rate, so their sections are scaled back to the time of the original map.
+fn columns(strains: &Strains, first_object: f64, length: f64, rate: f64) -> Vec<f64>{
+    let mut columns = vec![0.0; GRAPH_COLUMNS];
+    let section_len = strains.section_len();
+    if length <= 0.0 || section_len <= 0.0{
+        return columns;
+    }
+
+    // Every skill has a strain for each section, which are added up to get the strain of the whole section
+    let skills: Vec<&Vec<f64>> = match strains{
+        Strains::Osu(x) => vec![&x.aim, &x.speed],
+        Strains::Taiko(x) => vec![&x.color, &x.rhythm, &x.stamina],
+        Strains::Catch(x) => vec![&x.movement],
+        Strains::Mania(x) => vec![&x.strains],
+    };
+    // Sections are aligned to multiples of their length, starting with the one the first hit object is in
+    let first_section = (first_object / rate / section_len).floor() * section_len;
+    for section in 0..strains.len(){
+        let strain: f64 = skills.iter().filter_map(|x| x.get(section)).sum();
+        let time = (first_section + section as f64 * section_len) * rate;
+        let column = ((time / length * GRAPH_COLUMNS as f64) as usize).min(GRAPH_COLUMNS - 1);
+        columns[column] = columns[column].max(strain);
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use rosu_pp::mania::ManiaStrains;
+
+    /// Mania strains in sections of 400ms with a single spike in the given section.
+    fn spike(section: usize, len: usize) -> Strains{
+        let mut strains = vec![1.0; len];
+        strains[section] = 10.0;
+        Strains::Mania(ManiaStrains{ section_len: 400.0, strains })
+    }
+
+    fn spike_column(columns: &[f64]) -> usize{
+        columns.iter().position(|x| *x == 10.0).unwrap()
+    }
+
+    #[test]
+    fn strains_at_a_rate_line_up_with_the_original(){
+        // The spike is 6000ms into the original map, which is 4000ms into the map at 1.5x
+        let original = columns(&spike(15, 25), 0.0, 10000.0, 1.0);
+        let modified = columns(&spike(10, 17), 0.0, 10000.0, 1.5);
+        assert_eq!(spike_column(&original), 60);
+        assert_eq!(spike_column(&modified), 60);
+    }
+
+    #[test]
+    fn sections_at_a_rate_start_at_the_first_object(){
+        // The first object at 1200ms is 800ms into the map at 1.5x, which is the start of the third section
+        let modified = columns(&spike(0, 10), 1200.0, 6000.0, 1.5);
+        assert_eq!(spike_column(&modified), 20);
+    }
+}
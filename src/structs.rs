@@ -1,11 +1,11 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use dioxus::prelude::*;
 use serde_json::error::Category;
-use std::{path::PathBuf, io::{ErrorKind, Write, BufReader}, fs::File};
+use std::{path::{Path, PathBuf}, io::{ErrorKind, Write}, fs::File, fmt, str::FromStr, sync::Arc};
 use libosu::{data::Mode, events::Event::Background};
 use rosu_pp::BeatmapExt;
 use serde::{Serialize, Deserialize};
-use crate::utils::{calculate_bpm, round_dec};
+use crate::{fidelity::{find_losses, Loss}, timing::{ar_for_preempt, hit_windows, od_for_window, preempt, DifficultyTarget, TimedValue, Timing}, utils::{analyze_bpm, format_time, round_dec}};
 
 // #[derive(Clone)]
 // pub struct AppProps<'a>{
@@ -25,17 +25,46 @@ pub struct MapOptions{
     pub approach_rate: f64,
     pub artist: Box<str>,
     pub background: Option<PathBuf>,
+    pub background_options: BackgroundOptions,
+    /// The dominant bpm of the map
     pub bpm: usize,
+    pub bpm_info: BpmInfo,
     pub circle_size: f64,
+    pub convert_mode: Option<Mode>,
     pub difficulty_name: Box<str>,
     pub hp_drain: f64,
+    /// Position of the map in a training ladder and the amount of steps of the ladder
+    pub ladder_step: Option<(usize, usize)>,
+    pub ln_gap: u32,
+    pub long_notes: LongNotes,
     pub map_path: PathBuf,
+    pub mirror: Mirror,
     pub mode: Mode,
+    pub mods: Mods,
     pub original_ar: f64,
+    pub original_cs: f64,
+    pub original_hp: f64,
     pub original_od: f64,
+    /// The original map as it was read by read_map_metadata
+    pub loaded: Option<Arc<LoadedMap>>,
     pub overall_difficulty: f64,
+    /// Start and end time in milliseconds of the part of the map that is kept
+    pub practice_range: Option<(i32, i32)>,
+    /// Time in milliseconds the song select previews the song from
+    pub preview_time: i32,
+    pub random_seed: Option<u64>,
     pub rate: f64,
+    pub rotation: f64,
+    pub slider_multiplier: f64,
+    pub slider_tail_circles: bool,
+    pub slider_tick_rate: f64,
+    pub sliders_to_circles: bool,
+    pub spacing: f64,
+    pub spinners: Spinners,
+    pub stack_leniency: f64,
     pub stars: f64,
+    pub stream_thinning: Option<u32>,
+    pub strip_storyboard: bool,
     pub title: Box<str>,
 }
 
@@ -45,98 +74,518 @@ impl MapOptions{
             approach_rate: 5.0,
             artist: "".into(),
             background: None,
+            background_options: BackgroundOptions::default(),
             bpm: 100,
+            bpm_info: BpmInfo::default(),
             circle_size: 5.0,
+            convert_mode: None,
             difficulty_name: "".into(),
             hp_drain: 5.0,
+            ladder_step: None,
+            ln_gap: 4,
+            long_notes: LongNotes::Keep,
             map_path: PathBuf::new(), 
+            mirror: Mirror::None,
             mode: Mode::Osu,
+            mods: Mods::default(),
             original_ar: 5.0,
+            original_cs: 5.0,
+            original_hp: 5.0,
             original_od: 5.0,
+            loaded: None,
             overall_difficulty: 5.0,
+            practice_range: None,
+            preview_time: 0,
+            random_seed: None,
             rate: 1.0,
+            rotation: 0.0,
+            slider_multiplier: 1.4,
+            slider_tail_circles: false,
+            slider_tick_rate: 1.0,
+            sliders_to_circles: false,
+            spacing: 1.0,
+            spinners: Spinners::Keep,
+            stack_leniency: 0.7,
             stars: 0.0,
+            stream_thinning: None,
+            strip_storyboard: false,
             title: "".into(),
         }
     }
 
     /// Reads the data of a .osu map file and modifies the MapOptions struct accordingly
     pub fn read_map_metadata(&mut self, settings: &Settings) -> Result<()>{
-        let map = libosu::beatmap::Beatmap::parse(BufReader::new(File::open(settings.songs_path.join(&self.map_path))?))?;
-        let stars = rosu_pp::Beatmap::from_path(settings.songs_path.join(&self.map_path))?.stars().calculate().stars();
+        let loaded = LoadedMap::load(&settings.songs_path.join(&self.map_path))?;
+        self.read_loaded_metadata(Arc::new(loaded), settings);
+        Ok(())
+    }
+
+    /// Modifies the MapOptions struct according to an already loaded map and keeps the map for the
+    /// difficulty calculation and generation. The map path is only used to find the background of the map.
+    pub fn read_loaded_metadata(&mut self, loaded: Arc<LoadedMap>, settings: &Settings){
+        let map = &loaded.beatmap;
+        self.mode = map.mode;
         self.original_ar = map.difficulty.approach_rate;
+        self.original_cs = map.difficulty.circle_size;
+        self.original_hp = map.difficulty.hp_drain_rate;
         self.original_od = map.difficulty.overall_difficulty;
-        if !settings.ar_lock{
-            if settings.scale_ar{
-                self.scale_ar();
-            }else{
-                self.approach_rate = map.difficulty.approach_rate;
-            }
+        self.apply_mods(settings);
+        if !settings.sm_lock{
+            self.slider_multiplier = map.difficulty.slider_multiplier;
         }
-        if !settings.cs_lock{
-            self.circle_size = map.difficulty.circle_size;
-        }
-        if !settings.hp_lock{
-            self.hp_drain = map.difficulty.hp_drain_rate;
+        if !settings.tr_lock{
+            self.slider_tick_rate = map.difficulty.slider_tick_rate;
         }
-        if !settings.od_lock{
-            if settings.scale_od{
-                self.scale_od();
-            }else{
-                self.overall_difficulty = map.difficulty.overall_difficulty;
-            }
+        if !settings.sl_lock{
+            self.stack_leniency = map.stack_leniency;
         }
-        self.bpm = calculate_bpm(&map.timing_points);
+        self.bpm_info = analyze_bpm(&map.timing_points, map.hit_objects.last().map_or(0, |x| x.start_time.0));
+        self.bpm = self.bpm_info.dominant.round() as usize;
+        self.preview_time = map.preview_time.0;
+        // Practice ranges only make sense for the map they were chosen on
+        self.practice_range = None;
         self.background = {
             let mut bg = None;
-            for i in map.events{
+            for i in &map.events{
                 if let Background(b) = i{
-                    bg = Some(self.map_path.parent().unwrap().to_path_buf().join(PathBuf::from(b.filename)));
+                    bg = Some(self.map_path.parent().unwrap().to_path_buf().join(PathBuf::from(&b.filename)));
                     break;
                 }
             }
             bg
         };
-        self.mode = map.mode;
-        self.stars = round_dec(stars, 2);
-        self.title = map.title.into();
-        self.artist = map.artist.into();
-        self.difficulty_name = map.difficulty_name.into();
-        Ok(())
+        self.stars = round_dec(loaded.stars, 2);
+        self.title = map.title.as_str().into();
+        self.artist = map.artist.as_str().into();
+        self.difficulty_name = map.difficulty_name.as_str().into();
+        self.loaded = Some(loaded);
+    }
+
+    /// The loaded original map, or the map at the map path if no map was loaded yet or the path changed since.
+    pub fn loaded_map(&self, settings: &Settings) -> Result<Arc<LoadedMap>>{
+        let path = settings.songs_path.join(&self.map_path);
+        match &self.loaded{
+            Some(loaded) if loaded.path == path => Ok(loaded.clone()),
+            _ => Ok(Arc::new(LoadedMap::load(&path)?))
+        }
+    }
+
+    /// Short description of the mods and transforms applied to the map, used to name the generated map.
+    /// Returns None if the map is only changed by its rate and difficulty values.
+    pub fn variant_name(&self) -> Option<String>{
+        let mut parts: Vec<String> = Vec::new();
+        match self.target_mode(){
+            _ if self.target_mode() == self.mode => (),
+            Mode::Osu => (),
+            Mode::Taiko => parts.push("Taiko".to_string()),
+            Mode::Catch => parts.push("Catch".to_string()),
+            Mode::Mania => parts.push("Mania".to_string()),
+        }
+        if !self.mods.is_empty(){
+            parts.push(format!("+{}", self.mods));
+        }
+        if let Some((start, end)) = self.practice_range{
+            parts.push(format!("Practice {}-{}", format_time(start), format_time(end)));
+        }
+        if self.target_mode() == Mode::Osu{
+            if self.mirror != Mirror::None{
+                parts.push(format!("Mirror {}", self.mirror));
+            }
+            if self.rotation % 360.0 != 0.0{
+                parts.push(format!("Rotate {}", self.rotation));
+            }
+            if self.spacing != 1.0{
+                parts.push(format!("Spacing {}x", self.spacing));
+            }
+            if let Some(seed) = self.random_seed{
+                parts.push(format!("Random {}", seed));
+            }
+        }
+        if self.target_mode() == Mode::Mania{
+            // Converted maps get their key count from the conversion
+            if self.mode == Mode::Mania && self.circle_size != self.original_cs.round(){
+                parts.push(format!("{}K", self.circle_size));
+            }
+            if self.mirror == Mirror::Horizontal{
+                parts.push("Mirror".to_string());
+            }
+            match self.long_notes{
+                LongNotes::Keep => (),
+                LongNotes::Full => parts.push("Full LN".to_string()),
+                LongNotes::Remove => parts.push("No LN".to_string()),
+                LongNotes::Invert => parts.push("Invert".to_string()),
+            }
+            if let Some(seed) = self.random_seed{
+                parts.push(format!("Random {}", seed));
+            }
+        }else{
+            if self.sliders_to_circles{
+                parts.push("No Sliders".to_string());
+            }
+            match self.spinners{
+                Spinners::Keep => (),
+                Spinners::Remove => parts.push("No Spinners".to_string()),
+                Spinners::Shorten(max_length) => parts.push(format!("Spinners {}ms", max_length)),
+            }
+            if let Some(divisor) = self.stream_thinning{
+                parts.push(format!("Thinned 1/{}", divisor));
+            }
+        }
+        if parts.is_empty(){
+            None
+        }else{
+            Some(parts.join(" "))
+        }
+    }
+
+    /// Name of the step of a training ladder, padded so the names of all steps sort in order.
+    pub fn ladder_name(&self) -> Option<String>{
+        self.ladder_step.map(|(step, count)| format!("Step {:0width$}", step, width = count.to_string().len()))
+    }
+
+    /// The mode of the generated map, taking conversions into account.
+    /// Only osu!standard maps can be converted to other modes.
+    pub fn target_mode(&self) -> Mode{
+        match self.convert_mode{
+            Some(mode) if self.mode == Mode::Osu => mode,
+            _ => self.mode
+        }
+    }
+
+    /// The rate the map will be generated at, including the rate change of any selected mods.
+    pub fn effective_rate(&self) -> f64{
+        self.rate * self.mods.clock_rate()
+    }
+
+    /// Sets the difficulty values from the original map values with the selected mods applied.
+    /// Locked values are left untouched. The rate change of DT and HT always scales AR and OD like
+    /// it does in osu!, while the rate chosen by the user only does so if scaling is enabled.
+    pub fn apply_mods(&mut self, settings: &Settings){
+        if !settings.ar_lock{
+            self.approach_rate = self.ar_for_rate(if settings.scale_ar { self.effective_rate() } else { self.mods.clock_rate() });
+        }
+        if !settings.cs_lock{
+            self.circle_size = self.mods.adjust_cs(self.original_cs, self.target_mode());
+        }
+        if !settings.hp_lock{
+            self.hp_drain = self.mods.adjust(self.original_hp);
+        }
+        if !settings.od_lock{
+            self.overall_difficulty = self.od_for_rate(if settings.scale_od { self.effective_rate() } else { self.mods.clock_rate() });
+        }
     }
 
-    // Code logic copied from https://github.com/hwsmm/cosutrainer/blob/9bc998977976116c4cd2e559dc85d46cfeb191cd/src/mapeditor.c#L98
     /// Scales the approach rate with the given rate.
     pub fn scale_ar(&mut self){
-        match self.mode {
-            Mode::Taiko | Mode::Mania => (),
+        if let Mode::Osu | Mode::Catch = self.target_mode(){
+            self.approach_rate = self.ar_for_rate(self.effective_rate());
+        }
+    }
+
+    /// Scales the overall difficulty with the given rate.
+    pub fn scale_od(&mut self){
+        if let Mode::Osu | Mode::Taiko | Mode::Mania = self.target_mode(){
+            self.overall_difficulty = self.od_for_rate(self.effective_rate());
+        }
+    }
+
+    /// Approach and hit window timings of the generated map at its rate.
+    pub fn timing(&self) -> Timing{
+        Timing::new(self.target_mode(), self.approach_rate, self.overall_difficulty, self.effective_rate())
+    }
+
+    /// Sets the AR or OD in the map so that it results in the target once the map is played at its rate.
+    /// The value is clamped to what osu! accepts, and a warning is returned if the target can not be reached.
+    pub fn set_timed_value(&mut self, value: TimedValue, target: DifficultyTarget) -> Option<String>{
+        let rate = self.effective_rate();
+        let (name, field) = match value{
+            TimedValue::ApproachRate => ("approach rate", &mut self.approach_rate),
+            TimedValue::OverallDifficulty(_) => ("overall difficulty", &mut self.overall_difficulty),
+        };
+        let Some(map_value) = value.map_value(target, rate).map(|x| round_dec(x, 2)) else {
+            return Some(format!("The {} of {} maps can not be given in effective terms since they have no hit windows.", name, mode_name(self.target_mode())));
+        };
+        let clamped = map_value.min(10.0).max(0.0);
+        *field = clamped;
+        (clamped != map_value).then(|| format!("The {} {} can not be reached at {}x since it needs a value of {} in the map, using {} instead.", name, target, rate, map_value, clamped))
+    }
+
+    /// Whether the field has an effect on maps of the target mode.
+    pub fn uses_field(&self, field: MapField) -> bool{
+        let mode = self.target_mode();
+        match field{
+            MapField::ApproachRate => matches!(mode, Mode::Osu | Mode::Catch),
+            // Converted mania maps get their key count from the conversion
+            MapField::CircleSize => match mode{
+                Mode::Taiko => false,
+                Mode::Mania => self.mode == Mode::Mania,
+                Mode::Osu | Mode::Catch => true
+            },
+            MapField::OverallDifficulty => mode != Mode::Catch,
+            MapField::SliderMultiplier | MapField::SliderTickRate => mode != Mode::Mania,
+            MapField::StackLeniency | MapField::Transforms => mode == Mode::Osu,
+            MapField::LongNotes => mode == Mode::Mania,
+            MapField::Convert => self.mode == Mode::Osu,
+            // Generation only mirrors and randomizes osu!standard and osu!mania maps
+            MapField::Mirror | MapField::Random => matches!(mode, Mode::Osu | Mode::Mania),
+            MapField::HpDrain | MapField::Rate | MapField::PracticeRange | MapField::Fidelity | MapField::Storyboard => true,
+        }
+    }
+
+    /// Checks the options against the target mode of the map before it is generated.
+    /// Errors prevent the map from being generated while warnings only point out options that have no effect.
+    pub fn validate(&self, settings: &Settings) -> Vec<ValidationIssue>{
+        let mode = self.target_mode();
+        let mut issues = Vec::new();
+        let mut error = |field, message: String| issues.push(ValidationIssue{ field, severity: Severity::Error, message });
+
+        let ranges = [
+            (MapField::ApproachRate, "approach rate", self.approach_rate, 0.0, 10.0),
+            (MapField::CircleSize, "circle size", self.circle_size, 0.0, 10.0),
+            (MapField::HpDrain, "hp drain", self.hp_drain, 0.0, 10.0),
+            (MapField::OverallDifficulty, "overall difficulty", self.overall_difficulty, 0.0, 10.0),
+            (MapField::SliderMultiplier, "slider velocity", self.slider_multiplier, 0.4, 3.6),
+            (MapField::SliderTickRate, "slider tick rate", self.slider_tick_rate, 0.5, 8.0),
+            (MapField::StackLeniency, "stack leniency", self.stack_leniency, 0.0, 1.0),
+        ];
+        for (field, name, value, min, max) in ranges{
+            if !(min..=max).contains(&value){
+                error(field, format!("The {} must be between {} and {}, not {}.", name, min, max, value));
+            }
+        }
+        if self.rate <= 0.0 || !self.rate.is_finite(){
+            error(MapField::Rate, format!("The rate must be a positive number, not {}.", self.rate));
+        }
+        if mode == Mode::Mania && self.mode == Mode::Mania && (self.circle_size.fract() != 0.0 || !(1.0..=10.0).contains(&self.circle_size)){
+            error(MapField::CircleSize, format!("The key count of an osu!mania map must be a whole number between 1 and 10, not {}.", self.circle_size));
+        }
+        if mode == Mode::Mania && matches!(self.mirror, Mirror::Vertical | Mirror::Diagonal){
+            error(MapField::Mirror, "osu!mania maps can only be mirrored horizontally.".to_string());
+        }
+        if self.long_notes != LongNotes::Keep && !self.uses_field(MapField::LongNotes){
+            error(MapField::LongNotes, "Long note conversions only work on osu!mania maps.".to_string());
+        }
+        if self.convert_mode.is_some_and(|x| x != self.mode) && !self.uses_field(MapField::Convert){
+            error(MapField::Convert, "Only osu!standard maps can be converted to other modes.".to_string());
+        }
+        if self.practice_range.is_some_and(|(start, end)| start >= end){
+            error(MapField::PracticeRange, "The practice range must end after it starts.".to_string());
+        }
+        if self.spacing < 0.0{
+            error(MapField::Transforms, "The spacing factor can not be negative.".to_string());
+        }
+        // Events are lost on purpose if the storyboard is stripped
+        let losses = self.loaded.as_ref().map_or(&[][..], |x| x.losses.as_slice()).iter()
+            .filter(|x| !(self.strip_storyboard && x.is_storyboard()))
+            .collect::<Vec<&Loss>>();
+        let content_losses = losses.iter().filter(|x| x.is_content(self.strip_storyboard)).map(|x| x.to_string()).collect::<Vec<String>>();
+        if !content_losses.is_empty() && !settings.allow_lossy{
+            error(MapField::Fidelity, format!("Generating this map would lose content of the original map:\n{}\nAllow lossy generation in the settings to generate it anyway.", content_losses.join("\n")));
+        }
+
+        let mut warning = |field, message: String| issues.push(ValidationIssue{ field, severity: Severity::Warning, message });
+        if self.approach_rate != self.mods.adjust(self.original_ar) && !self.uses_field(MapField::ApproachRate){
+            warning(MapField::ApproachRate, format!("The approach rate has no effect on {} maps.", mode_name(mode)));
+        }
+        if self.overall_difficulty != self.mods.adjust(self.original_od) && !self.uses_field(MapField::OverallDifficulty){
+            warning(MapField::OverallDifficulty, format!("The overall difficulty has no effect on {} maps.", mode_name(mode)));
+        }
+        if self.circle_size != self.mods.adjust_cs(self.original_cs, mode) && !self.uses_field(MapField::CircleSize){
+            warning(MapField::CircleSize, format!("The circle size has no effect on {} maps.", mode_name(mode)));
+        }
+        if settings.scale_ar && !self.uses_field(MapField::ApproachRate){
+            warning(MapField::ApproachRate, format!("AR scaling does nothing for {} maps.", mode_name(mode)));
+        }
+        if settings.scale_od && !self.uses_field(MapField::OverallDifficulty){
+            warning(MapField::OverallDifficulty, format!("OD scaling does nothing for {} maps.", mode_name(mode)));
+        }
+        if self.mirror != Mirror::None && !self.uses_field(MapField::Mirror){
+            warning(MapField::Mirror, format!("Mirroring only works on osu!standard and osu!mania maps, so the {} map is not mirrored.", mode_name(mode)));
+        }
+        if self.random_seed.is_some() && !self.uses_field(MapField::Random){
+            warning(MapField::Random, format!("Randomizing only works on osu!standard and osu!mania maps, so the {} map is not randomized.", mode_name(mode)));
+        }
+        if (self.rotation % 360.0 != 0.0 || self.spacing != 1.0) && !self.uses_field(MapField::Transforms){
+            warning(MapField::Transforms, "Rotation and spacing only work on osu!standard maps.".to_string());
+        }
+        if let Some(storyboard) = self.loaded.as_ref().and_then(|x| x.storyboard.as_ref()).filter(|_| self.strip_storyboard){
+            warning(MapField::Storyboard, format!("The storyboard in {} is shared by the whole mapset, so osu! still shows it with the generated map. It is not added to the .osz file.", storyboard.file_name().unwrap_or_default().to_string_lossy()));
+        }
+        // Content losses are errors unless lossy generation is allowed, the other losses only change how the map is written
+        for loss in losses.iter().filter(|x| settings.allow_lossy || !x.is_content(self.strip_storyboard)){
+            warning(MapField::Fidelity, format!("{}.", loss));
+        }
+        issues
+    }
+
+    /// Creates a rosu-pp beatmap of the original map with the difficulty values of the generated map.
+    /// AR and OD are converted back to the original rate since rosu-pp applies the rate itself.
+    pub fn difficulty_beatmap(&self, settings: &Settings) -> Result<rosu_pp::Beatmap>{
+        let mut beatmap = self.loaded_map(settings)?.difficulty.clone();
+        self.apply_difficulty(&mut beatmap);
+        Ok(beatmap)
+    }
+
+    /// Whether the transforms change the objects of the generated map in a way that changes its difficulty.
+    /// The star rating and pp are calculated from the objects of the original map, so they only account for
+    /// the difficulty values and the rate of the generated map if this is true.
+    pub fn changes_objects(&self) -> bool{
+        let mode = self.target_mode();
+        self.practice_range.is_some()
+            || (matches!(mode, Mode::Osu | Mode::Mania) && self.random_seed.is_some())
+            || (mode == Mode::Osu && self.spacing != 1.0)
+            || (mode != Mode::Mania && (self.stream_thinning.is_some() || self.sliders_to_circles || self.spinners != Spinners::Keep))
+            || (mode == Mode::Mania && self.long_notes != LongNotes::Keep)
+            || (mode == Mode::Mania && self.mode == Mode::Mania && self.circle_size != self.mods.adjust_cs(self.original_cs, mode))
+    }
+
+    /// Sets the difficulty values of the generated map on a rosu-pp beatmap of the original map.
+    fn apply_difficulty(&self, beatmap: &mut rosu_pp::Beatmap){
+        let rate = self.effective_rate();
+        beatmap.ar = unscale_ar(self.approach_rate, rate) as f32;
+        beatmap.od = unscale_od(self.overall_difficulty, rate, self.target_mode()) as f32;
+        beatmap.hp = self.hp_drain as f32;
+        // Converted mania maps get their key count from the conversion
+        if self.uses_field(MapField::CircleSize){
+            beatmap.cs = self.circle_size as f32;
+        }
+        // The generated map scales the length of its sliders with the slider velocity
+        let length_factor = self.slider_multiplier / beatmap.slider_mult;
+        if length_factor != 1.0{
+            for h in beatmap.hit_objects.iter_mut(){
+                if let rosu_pp::parse::HitObjectKind::Slider{ pixel_len: Some(pixel_len), .. } = &mut h.kind{
+                    *pixel_len *= length_factor;
+                }
+            }
+        }
+        beatmap.slider_mult = self.slider_multiplier;
+        beatmap.tick_rate = self.slider_tick_rate;
+        beatmap.stack_leniency = self.stack_leniency as f32;
+    }
+
+    /// Calculates the star rating of the generated map from an already parsed rosu-pp beatmap of the original map.
+    fn stars_for(&self, original: &rosu_pp::Beatmap) -> f64{
+        let mut beatmap = original.clone();
+        self.apply_difficulty(&mut beatmap);
+        let stars = beatmap.stars()
+            .mode(game_mode(self.target_mode()))
+            .clock_rate(self.effective_rate())
+            .calculate()
+            .stars();
+        round_dec(stars, 2)
+    }
+
+    /// Searches for the rate at which the generated map has the target star rating.
+    /// AR and OD are scaled with the rate like the rate slider does if scaling is enabled and they are not locked,
+    /// which keeps them between 0 and 10. Returns the star rating of the generated map at the found rate.
+    pub fn solve_rate_for_stars(&mut self, settings: &Settings, target: f64) -> Result<f64>{
+        if target <= 0.0 || !target.is_finite(){
+            return Err(anyhow!("The target star rating must be a positive number, not {}.", target));
+        }
+        let loaded = self.loaded_map(settings)?;
+        let beatmap = &loaded.difficulty;
+        let mut options = self.clone();
+        // Rates are searched in hundredths since that is the precision they are shown with
+        let (min, max) = STAR_SOLVER_RATES;
+        let mut stars_at = |rate: u32|{
+            options.set_rate(rate as f64 / 100.0, settings);
+            options.stars_for(beatmap)
+        };
+
+        let (min_stars, max_stars) = (stars_at(min), stars_at(max));
+        if max_stars < target - STAR_SOLVER_TOLERANCE{
+            return Err(anyhow!("The map can only reach {} stars, at {}x.", max_stars, max as f64 / 100.0));
+        }
+        if min_stars > target + STAR_SOLVER_TOLERANCE{
+            return Err(anyhow!("The map has at least {} stars, at {}x.", min_stars, min as f64 / 100.0));
+        }
+
+        // The star rating rises with the rate, so find the lowest rate that reaches the target
+        let (mut low, mut high) = (min, max);
+        while low < high{
+            let mid = (low + high) / 2;
+            if stars_at(mid) < target{
+                low = mid + 1;
+            }else{
+                high = mid;
+            }
+        }
+        // The rate just below might be closer to the target
+        let (mut rate, mut stars) = (low, stars_at(low));
+        if low > min{
+            let below = stars_at(low - 1);
+            if (target - below).abs() < (stars - target).abs(){
+                (rate, stars) = (low - 1, below);
+            }
+        }
+
+        options.set_rate(rate as f64 / 100.0, settings);
+        *self = options;
+        Ok(stars)
+    }
+
+    /// Changes the rate and scales AR and OD with it if scaling is enabled and they are not locked.
+    pub fn set_rate(&mut self, rate: f64, settings: &Settings){
+        self.rate = rate;
+        if settings.scale_ar && !settings.ar_lock{
+            self.scale_ar();
+        }
+        if settings.scale_od && !settings.od_lock{
+            self.scale_od();
+        }
+    }
+
+    /// Calculates the star rating and pp values of the generated map.
+    /// The pp values are calculated for the usual accuracies and for the accuracy and misses in the settings.
+    pub fn performance(&self, settings: &Settings) -> Result<Performance>{
+        let beatmap = self.difficulty_beatmap(settings)?;
+        let mode = game_mode(self.target_mode());
+        let rate = self.effective_rate();
+        let difficulty = beatmap.stars().mode(mode).clock_rate(rate).calculate();
+        let pp = |accuracy: f64, misses: u32| round_dec(beatmap.pp()
+            .mode(mode)
+            .clock_rate(rate)
+            .attributes(difficulty.clone())
+            .accuracy(accuracy)
+            .n_misses(misses as usize)
+            .calculate()
+            .pp(), 2);
+        Ok(Performance{
+            stars: round_dec(difficulty.stars(), 2),
+            pp: Performance::ACCURACIES.map(|accuracy| pp(accuracy, 0)),
+            custom_pp: pp(settings.pp_accuracy, settings.pp_misses),
+        })
+    }
+
+    // Code logic copied from https://github.com/hwsmm/cosutrainer/blob/9bc998977976116c4cd2e559dc85d46cfeb191cd/src/mapeditor.c#L98
+    /// Calculates the approach rate that feels like the original (mod adjusted) approach rate played at the given rate.
+    fn ar_for_rate(&self, rate: f64) -> f64{
+        let original_ar = self.mods.adjust(self.original_ar);
+        match self.target_mode() {
+            Mode::Taiko | Mode::Mania => original_ar,
             Mode::Osu | Mode::Catch => {
-                let mut ar_ms = if self.original_ar <= 5.0 {
-                     1200.0 + 600.0 * (5.0 - self.original_ar) / 5.0
+                let mut ar_ms = if original_ar <= 5.0 {
+                     1200.0 + 600.0 * (5.0 - original_ar) / 5.0
                 }else{
-                     1200.0 - 750.0 * (self.original_ar - 5.0) / 5.0
+                     1200.0 - 750.0 * (original_ar - 5.0) / 5.0
                 };
-                ar_ms /= self.rate;
+                ar_ms /= rate;
 
-                self.approach_rate = round_dec(if ar_ms >= 1200.0 {
+                round_dec(if ar_ms >= 1200.0 {
                     15.0 - ar_ms / 120.0 
                 }else{
                     (1200.0 / 150.0) - (ar_ms / 150.0) + 5.0
-                }, 2).min(10.0).max(0.0);
+                }, 2).min(10.0).max(0.0)
                 // Added min and max to keep the ar within a valid range
             }
         }
     }
 
-    // Code logic copied from https://github.com/hwsmm/cosutrainer/blob/9bc998977976116c4cd2e559dc85d46cfeb191cd/src/mapeditor.c#L108
-    /// Scales the overall difficulty with the given rate.
-    pub fn scale_od(&mut self){
-        self.overall_difficulty = round_dec(match self.mode{
-            Mode::Osu => (80.0 - (80.0 - 6.0 * self.original_od) / self.rate) / 6.0,
-            Mode::Taiko => (80.0 - (80.0 - 6.0 * self.original_od) / self.rate) / 6.0,
-            Mode::Catch => self.overall_difficulty,
-            Mode::Mania => (64.0 - (64.0 - 3.0 * self.original_od) / self.rate) / 3.0,
-        }, 2).min(10.0).max(0.0);
+    /// Calculates the overall difficulty that feels like the original (mod adjusted) overall difficulty played at the given rate.
+    /// The hit window of the best judgement of the target mode is scaled, the same way the effective OD is calculated.
+    fn od_for_rate(&self, rate: f64) -> f64{
+        let original_od = self.mods.adjust(self.original_od);
+        round_dec(scale_od_window(original_od, 1.0 / rate, self.target_mode()), 2).min(10.0).max(0.0)
         // Added min and max to keep the od within a valid range
     }
 }
@@ -147,9 +596,521 @@ impl Default for MapOptions{
     }
 }
 
+/// A map file that is read and parsed once, then shared by the metadata, the difficulty calculation and the generation
+pub struct LoadedMap{
+    pub path: PathBuf,
+    /// Contents of the file, used to check for content that libosu would lose
+    pub source: String,
+    pub beatmap: libosu::beatmap::Beatmap,
+    /// The map as parsed by rosu-pp for the difficulty calculation
+    pub difficulty: rosu_pp::Beatmap,
+    /// Star rating of the unmodified map
+    pub stars: f64,
+    /// Content of the file that would be lost or written differently when the map is generated
+    pub losses: Vec<Loss>,
+    /// Storyboard file (.osb) in the folder of the map, which osu! shows with every map of the mapset
+    pub storyboard: Option<PathBuf>,
+}
+
+impl LoadedMap{
+    pub fn load(path: &Path) -> Result<Self>{
+        // Maps that are not valid UTF-8 are still read, with the invalid characters replaced
+        Self::parse(path, String::from_utf8_lossy(&std::fs::read(path)?).into_owned())
+    }
+
+    /// Parses the contents of a map file. The path is only kept to identify the map.
+    pub fn parse(path: &Path, source: String) -> Result<Self>{
+        let beatmap = libosu::beatmap::Beatmap::parse(source.as_bytes())?;
+        let difficulty = rosu_pp::Beatmap::from_bytes(source.as_bytes())?;
+        let stars = difficulty.stars().calculate().stars();
+        let losses = find_losses(&source, &beatmap.to_string());
+        let storyboard = path.parent().and_then(|x| std::fs::read_dir(x).ok()).and_then(|mut x| x.find_map(|entry|{
+            let entry = entry.ok()?.path();
+            entry.extension().is_some_and(|x| x.eq_ignore_ascii_case("osb")).then_some(entry)
+        }));
+        Ok(LoadedMap{ path: path.to_path_buf(), source, beatmap, difficulty, stars, losses, storyboard })
+    }
+}
+
+// Loaded maps are compared on every change of MapOptions, so only the file is compared and Eq lets Arc compare pointers first
+impl PartialEq for LoadedMap{
+    fn eq(&self, other: &Self) -> bool{
+        self.path == other.path && self.source == other.source
+    }
+}
+
+impl Eq for LoadedMap{}
+
+impl fmt::Debug for LoadedMap{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        f.debug_struct("LoadedMap").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+/// Path and contents of a generated map. The map is only written to the path if no .osz file is generated
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedMap{
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Paths of the files that are written when a map is generated
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputPaths{
+    /// The .osu file, which is only written if no .osz file is generated
+    pub map: PathBuf,
+    /// The audio file at the rate of the map, which is only written if it does not exist yet or its generation is forced
+    pub audio: PathBuf,
+    pub background: Option<PathBuf>,
+    pub osz: Option<PathBuf>,
+}
+
+/// Star rating and pp values of a generated map
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Performance{
+    pub stars: f64,
+    /// pp values for each of the accuracies in Performance::ACCURACIES without misses
+    pub pp: [f64; 4],
+    /// pp value for the accuracy and misses in the settings
+    pub custom_pp: f64,
+}
+
+impl Performance{
+    pub const ACCURACIES: [f64; 4] = [100.0, 99.0, 98.0, 95.0];
+}
+
+/// Lowest and highest rate in hundredths that the star rating solver tries
+const STAR_SOLVER_RATES: (u32, u32) = (25, 400);
+/// How far the star rating found by the solver may be from the target
+const STAR_SOLVER_TOLERANCE: f64 = 0.01;
+
+/// The rosu-pp equivalent of a libosu mode
+pub fn game_mode(mode: Mode) -> rosu_pp::GameMode{
+    match mode{
+        Mode::Osu => rosu_pp::GameMode::Osu,
+        Mode::Taiko => rosu_pp::GameMode::Taiko,
+        Mode::Catch => rosu_pp::GameMode::Catch,
+        Mode::Mania => rosu_pp::GameMode::Mania
+    }
+}
+
+/// Calculates the approach rate that results in the given approach rate once the map is played at the given rate.
+fn unscale_ar(ar: f64, rate: f64) -> f64{
+    ar_for_preempt(preempt(ar) * rate)
+}
+
+/// Calculates the overall difficulty that results in the given overall difficulty once the map is played at the given rate.
+fn unscale_od(od: f64, rate: f64, mode: Mode) -> f64{
+    scale_od_window(od, rate, mode)
+}
+
+/// Overall difficulty whose window for the best judgement is the window of the given overall difficulty multiplied by the factor.
+/// osu!catch has no hit windows, so its overall difficulty is returned unchanged.
+fn scale_od_window(od: f64, factor: f64, mode: Mode) -> f64{
+    hit_windows(od, mode).first()
+        .and_then(|(_, window)| od_for_window(window * factor, mode))
+        .unwrap_or(od)
+}
+
+/// osu! mods that can be baked into a generated map.
+/// The map is written so that playing it with no mods plays like the original with these mods.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Mods{
+    pub double_time: bool,
+    pub easy: bool,
+    pub half_time: bool,
+    pub hard_rock: bool,
+}
+
+impl Mods{
+    /// Returns true if no mods are selected.
+    pub fn is_empty(&self) -> bool{
+        !(self.double_time || self.easy || self.half_time || self.hard_rock)
+    }
+
+    /// The rate change caused by the selected mods.
+    pub fn clock_rate(&self) -> f64{
+        if self.double_time{
+            1.5
+        }else if self.half_time{
+            0.75
+        }else{
+            1.0
+        }
+    }
+
+    /// Applies the HR or EZ multiplier to AR, HP or OD, capped like osu! does.
+    pub fn adjust(&self, value: f64) -> f64{
+        if self.hard_rock{
+            (value * 1.4).min(10.0)
+        }else if self.easy{
+            value * 0.5
+        }else{
+            value
+        }
+    }
+
+    /// Applies the HR or EZ multiplier to CS. Mania key counts are never changed.
+    pub fn adjust_cs(&self, value: f64, mode: Mode) -> f64{
+        match mode{
+            Mode::Mania => value,
+            _ if self.hard_rock => (value * 1.3).min(10.0),
+            _ if self.easy => value * 0.5,
+            _ => value
+        }
+    }
+}
+
+impl fmt::Display for Mods{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        if self.easy{
+            write!(f, "EZ")?;
+        }
+        if self.hard_rock{
+            write!(f, "HR")?;
+        }
+        if self.half_time{
+            write!(f, "HT")?;
+        }
+        if self.double_time{
+            write!(f, "DT")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Mods{
+    type Err = anyhow::Error;
+
+    /// Parses a string of mod acronyms such as "HRDT". Letter case is ignored.
+    fn from_str(s: &str) -> Result<Self>{
+        let mut mods = Mods::default();
+        let s = s.to_uppercase();
+        if s.len() % 2 != 0{
+            return Err(anyhow!("Invalid mods: '{}'. Mods must be given as two letter acronyms, e.g. 'HRDT'.", s));
+        }
+        for i in (0..s.len()).step_by(2){
+            match s.get(i..i+2){
+                Some("EZ") => mods.easy = true,
+                Some("HR") => mods.hard_rock = true,
+                Some("HT") => mods.half_time = true,
+                Some("DT") | Some("NC") => mods.double_time = true,
+                Some("NM") => (),
+                _ => return Err(anyhow!("Unsupported mod in '{}'. Supported mods are EZ, HR, HT and DT.", s))
+            }
+        }
+        if mods.easy && mods.hard_rock{
+            return Err(anyhow!("EZ and HR can not be used together."));
+        }
+        if mods.half_time && mods.double_time{
+            return Err(anyhow!("HT and DT can not be used together."));
+        }
+        Ok(mods)
+    }
+}
+
+/// Options of MapOptions that are checked by the validation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MapField{
+    ApproachRate,
+    CircleSize,
+    Convert,
+    HpDrain,
+    LongNotes,
+    Mirror,
+    OverallDifficulty,
+    PracticeRange,
+    /// The random seed of the randomizing transforms
+    Random,
+    /// Content of the original map that libosu can not write
+    Fidelity,
+    Rate,
+    SliderMultiplier,
+    SliderTickRate,
+    StackLeniency,
+    /// Removal of the video and storyboard
+    Storyboard,
+    /// Rotation and spacing
+    Transforms,
+}
+
+/// Whether a ValidationIssue prevents the map from being generated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity{
+    Warning,
+    Error
+}
+
+/// A problem with MapOptions found by MapOptions::validate
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationIssue{
+    pub field: MapField,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Name of the mode as shown in osu!
+pub fn mode_name(mode: Mode) -> &'static str{
+    match mode{
+        Mode::Osu => "osu!standard",
+        Mode::Taiko => "osu!taiko",
+        Mode::Catch => "osu!catch",
+        Mode::Mania => "osu!mania"
+    }
+}
+
+/// Result of the bpm analysis of a map
+#[derive(Clone, Debug, PartialEq)]
+pub struct BpmInfo{
+    /// The bpm that lasts the longest
+    pub dominant: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Start time and bpm of every section of the map with a different bpm
+    pub sections: Vec<(i32, f64)>,
+}
+
+impl BpmInfo{
+    /// The bpm that is used when a map is changed to a target bpm.
+    pub fn target(&self, target: BpmTarget) -> f64{
+        match target{
+            BpmTarget::Dominant => self.dominant,
+            BpmTarget::Min => self.min,
+            BpmTarget::Max => self.max,
+        }
+    }
+}
+
+impl Default for BpmInfo{
+    fn default() -> Self{
+        BpmInfo{
+            dominant: 100.0,
+            min: 100.0,
+            max: 100.0,
+            sections: Vec::new(),
+        }
+    }
+}
+
+/// Which bpm of a map is changed to the bpm chosen by the user
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BpmTarget{
+    #[default]
+    Dominant,
+    Min,
+    Max,
+}
+
+impl FromStr for BpmTarget{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self>{
+        match s.to_lowercase().as_str(){
+            "dominant" => Ok(BpmTarget::Dominant),
+            "min" => Ok(BpmTarget::Min),
+            "max" => Ok(BpmTarget::Max),
+            _ => Err(anyhow!("Invalid bpm target: '{}'. Valid targets are 'dominant', 'min' and 'max'.", s))
+        }
+    }
+}
+
+/// Axis to mirror an osu!standard map along.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mirror{
+    #[default]
+    None,
+    Horizontal,
+    Vertical,
+    /// Mirrors along both axes
+    Diagonal,
+}
+
+impl fmt::Display for Mirror{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        write!(f, "{}", match self{
+            Mirror::None => "None",
+            Mirror::Horizontal => "H",
+            Mirror::Vertical => "V",
+            Mirror::Diagonal => "D",
+        })
+    }
+}
+
+impl FromStr for Mirror{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self>{
+        match s.to_lowercase().as_str(){
+            "none" | "n" => Ok(Mirror::None),
+            "horizontal" | "h" => Ok(Mirror::Horizontal),
+            "vertical" | "v" => Ok(Mirror::Vertical),
+            "diagonal" | "d" => Ok(Mirror::Diagonal),
+            _ => Err(anyhow!("Invalid mirror axis: '{}'. Valid axes are 'h', 'v' and 'd'.", s))
+        }
+    }
+}
+
+/// What to do with the spinners of a map.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Spinners{
+    #[default]
+    Keep,
+    Remove,
+    /// Shortens spinners to the given length in milliseconds
+    Shorten(u32),
+}
+
+impl FromStr for Spinners{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self>{
+        match s.to_lowercase().as_str(){
+            "keep" => Ok(Spinners::Keep),
+            "remove" => Ok(Spinners::Remove),
+            length => match length.parse::<u32>(){
+                Ok(k) => Ok(Spinners::Shorten(k)),
+                Err(_) => Err(anyhow!("Invalid spinner option: '{}'. Valid options are 'keep', 'remove' or a length in milliseconds.", s))
+            }
+        }
+    }
+}
+
+/// What to do with the long notes of an osu!mania map.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LongNotes{
+    #[default]
+    Keep,
+    /// Turns every note into a long note
+    Full,
+    /// Turns every long note into a regular note
+    Remove,
+    /// Turns the gaps between notes into long notes and vice versa
+    Invert,
+}
+
+impl FromStr for LongNotes{
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self>{
+        match s.to_lowercase().as_str(){
+            "keep" => Ok(LongNotes::Keep),
+            "full" => Ok(LongNotes::Full),
+            "none" | "remove" => Ok(LongNotes::Remove),
+            "invert" => Ok(LongNotes::Invert),
+            _ => Err(anyhow!("Invalid long note option: '{}'. Valid options are 'keep', 'full', 'none' and 'invert'.", s))
+        }
+    }
+}
+
+/// Changes made to the background image of generated maps
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackgroundOptions{
+    /// Standard deviation of the gaussian blur, no blur is applied if it is 0
+    pub blur: f32,
+    /// How much the background is darkened in percent
+    pub dim: u8,
+    /// Backgrounds taller than this are downscaled to this height
+    pub max_height: Option<u32>,
+    pub replacement: Option<BackgroundReplacement>,
+}
+
+impl BackgroundOptions{
+    /// Returns true if the background is left unchanged.
+    pub fn is_empty(&self) -> bool{
+        *self == Self::default()
+    }
+}
+
+/// What the background of generated maps is replaced with
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackgroundReplacement{
+    Colour(Colour),
+    Image(PathBuf),
+}
+
+impl FromStr for BackgroundReplacement{
+    type Err = anyhow::Error;
+
+    /// Parses a colour or, if that fails, the path to an image.
+    fn from_str(s: &str) -> Result<Self>{
+        if let Ok(colour) = s.parse::<Colour>(){
+            return Ok(BackgroundReplacement::Colour(colour));
+        }
+        let path = PathBuf::from(s);
+        if !path.is_file(){
+            return Err(anyhow!("Invalid background: '{}' is neither a colour nor an existing image.", s));
+        }
+        Ok(BackgroundReplacement::Image(path))
+    }
+}
+
+/// Metadata and appearance values that replace those of the original map in generated maps.
+/// Values that are not set are copied from the original map.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapOverrides{
+    pub artist_unicode: Option<String>,
+    pub combo_colours: Vec<Colour>,
+    pub countdown: Option<bool>,
+    pub creator: Option<String>,
+    pub letterbox_in_breaks: Option<bool>,
+    pub slider_border: Option<Colour>,
+    pub slider_track: Option<Colour>,
+    pub source: Option<String>,
+    pub tags: Option<String>,
+    pub title_unicode: Option<String>,
+    pub widescreen_storyboard: Option<bool>,
+}
+
+/// An RGB colour as used in the [Colours] section of a map
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Colour(pub u8, pub u8, pub u8);
+
+impl Colour{
+    /// Formats the colour the way it is written in .osu files.
+    pub fn to_osu(&self) -> String{
+        format!("{},{},{}", self.0, self.1, self.2)
+    }
+}
+
+impl fmt::Display for Colour{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl FromStr for Colour{
+    type Err = anyhow::Error;
+
+    /// Parses colours in either the '#rrggbb' or the 'r,g,b' format.
+    fn from_str(s: &str) -> Result<Self>{
+        let invalid = || anyhow!("Invalid colour: '{}'. Use either the '#rrggbb' or the 'r,g,b' format.", s);
+        if let Some(hex) = s.strip_prefix('#'){
+            if hex.len() != 6 || !hex.is_ascii(){
+                return Err(invalid());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i+2], 16).map_err(|_| invalid());
+            Ok(Colour(channel(0)?, channel(2)?, channel(4)?))
+        }else{
+            let channels = s.split(',').map(|x| x.trim().parse::<u8>().map_err(|_| invalid())).collect::<Result<Vec<u8>>>()?;
+            match channels[..]{
+                [r, g, b] => Ok(Colour(r, g, b)),
+                _ => Err(invalid())
+            }
+        }
+    }
+}
 
 #[derive(Debug, Props, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings{
+    pub allow_lossy: bool,
     pub ar_lock: bool,
     pub change_pitch: bool,
     pub cs_lock: bool,
@@ -159,10 +1120,16 @@ pub struct Settings{
     pub gosumemory_startup: bool,
     pub hp_lock: bool,
     pub od_lock: bool,
+    pub overrides: MapOverrides,
+    pub pp_accuracy: f64,
+    pub pp_misses: u32,
     pub scale_ar: bool,
     pub scale_od: bool,
+    pub sl_lock: bool,
+    pub sm_lock: bool,
     pub songs_path: PathBuf,
     pub theme: Theme,
+    pub tr_lock: bool,
     pub websocket_url: String,
 }
 
@@ -170,15 +1137,22 @@ impl Settings{
     pub fn new() -> Self{
         Settings{
             theme: Theme::Dark,
+            allow_lossy: false,
             ar_lock: false,
             change_pitch: true,
             cs_lock: false,
             force_generation: false,
             hp_lock: false,
             od_lock: false,
+            overrides: MapOverrides::default(),
+            pp_accuracy: 97.0,
+            pp_misses: 0,
             generate_osz: true,
             scale_ar: false,
             scale_od: false,
+            sl_lock: false,
+            sm_lock: false,
+            tr_lock: false,
             songs_path: PathBuf::new(),
             gosumemory_path: PathBuf::new(),
             gosumemory_startup: false,
@@ -280,3 +1254,96 @@ pub enum Tab{
     Manual,
     Settings
 }
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn scaled_od_is_the_effective_od_at_the_rate(){
+        for mode in [Mode::Osu, Mode::Taiko, Mode::Mania]{
+            let mut map = MapOptions::new();
+            map.mode = mode;
+            map.original_od = 5.0;
+            map.rate = 1.5;
+            map.scale_od();
+            let (effective, _) = TimedValue::OverallDifficulty(mode).effective(5.0, 1.5).unwrap();
+            assert_eq!(map.overall_difficulty, effective, "{}", mode_name(mode));
+        }
+    }
+
+    #[test]
+    fn taiko_od_is_scaled_with_the_taiko_hit_window(){
+        let mut map = MapOptions::new();
+        map.mode = Mode::Taiko;
+        map.original_od = 5.0;
+        map.rate = 1.5;
+        map.scale_od();
+        assert_eq!(map.overall_difficulty, 8.89);
+    }
+
+    #[test]
+    fn effective_od_targets_round_trip_through_the_map_od(){
+        for mode in [Mode::Osu, Mode::Taiko, Mode::Mania]{
+            let mut map = MapOptions::new();
+            map.mode = mode;
+            map.overall_difficulty = 6.0;
+            map.rate = 1.2;
+            let effective = map.timing().effective_od().unwrap();
+            assert_eq!(map.set_timed_value(TimedValue::OverallDifficulty(mode), DifficultyTarget::Effective(effective)), None);
+            assert!((map.overall_difficulty - 6.0).abs() <= 0.01, "{}: {}", mode_name(mode), map.overall_difficulty);
+        }
+    }
+
+    #[test]
+    fn parses_mod_acronyms(){
+        assert_eq!("hrdt".parse::<Mods>().unwrap(), Mods{ hard_rock: true, double_time: true, ..Mods::default() });
+        assert_eq!("EZNC".parse::<Mods>().unwrap(), Mods{ easy: true, double_time: true, ..Mods::default() });
+        assert_eq!("NM".parse::<Mods>().unwrap(), Mods::default());
+        assert_eq!("".parse::<Mods>().unwrap(), Mods::default());
+    }
+
+    #[test]
+    fn rejects_invalid_mods(){
+        for mods in ["EZHR", "HTDT", "HRD", "FL", "HR DT"]{
+            assert!(mods.parse::<Mods>().is_err(), "{}", mods);
+        }
+    }
+
+    #[test]
+    fn mods_display_as_they_are_parsed(){
+        for mods in ["EZHT", "HRDT", "DT", ""]{
+            assert_eq!(mods.parse::<Mods>().unwrap().to_string(), mods);
+        }
+    }
+
+    #[test]
+    fn parses_colours(){
+        assert_eq!("#ff8000".parse::<Colour>().unwrap(), Colour(255, 128, 0));
+        assert_eq!("#FF8000".parse::<Colour>().unwrap(), Colour(255, 128, 0));
+        assert_eq!("255, 128,0".parse::<Colour>().unwrap(), Colour(255, 128, 0));
+        assert_eq!(Colour(255, 128, 0).to_string(), "#ff8000");
+    }
+
+    #[test]
+    fn rejects_invalid_colours(){
+        for colour in ["", "#ff80", "#ff80000", "#gg0000", "#\u{e9}\u{e9}\u{e9}", "ff8000", "256,0,0", "1,2", "1,2,3,4", "-1,0,0"]{
+            assert!(colour.parse::<Colour>().is_err(), "{}", colour);
+        }
+    }
+
+    #[test]
+    fn mirror_and_random_warn_on_taiko_and_catch(){
+        for (mode, used) in [(Mode::Osu, true), (Mode::Taiko, false), (Mode::Catch, false), (Mode::Mania, true)]{
+            let mut map = MapOptions::new();
+            map.mode = mode;
+            map.mirror = Mirror::Horizontal;
+            map.random_seed = Some(1);
+            let fields = map.validate(&Settings::new()).into_iter().filter(|x| x.severity == Severity::Warning).map(|x| x.field).collect::<Vec<MapField>>();
+            assert_eq!(map.uses_field(MapField::Mirror), used);
+            assert_eq!(map.uses_field(MapField::Random), used);
+            assert_eq!(fields.contains(&MapField::Mirror), !used, "{}", mode_name(mode));
+            assert_eq!(fields.contains(&MapField::Random), !used, "{}", mode_name(mode));
+        }
+    }
+}
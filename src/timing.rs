@@ -0,0 +1,250 @@
+use std::{fmt, str::FromStr};
+use anyhow::{Result, anyhow};
+use libosu::data::Mode;
+use crate::utils::round_dec;
+
+/// Approach and hit window timings of a map in milliseconds, as written in the map and as the player experiences
+/// them at the rate the map is played at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timing{
+    pub rate: f64,
+    /// Time an object is visible before it has to be hit. Only osu!standard and osu!catch have an approach rate
+    pub preempt: Option<f64>,
+    /// Time an object takes to fade in. Only osu!standard has a fade in
+    pub fade_in: Option<f64>,
+    /// Names of the judgements and the time before or after an object in which they are given, from best to worst
+    pub hit_windows: Vec<(&'static str, f64)>,
+    mode: Mode,
+}
+
+impl Timing{
+    pub fn new(mode: Mode, approach_rate: f64, overall_difficulty: f64, rate: f64) -> Self{
+        Self{
+            rate,
+            preempt: matches!(mode, Mode::Osu | Mode::Catch).then(|| preempt(approach_rate)),
+            fade_in: (mode == Mode::Osu).then(|| fade_in(approach_rate)),
+            hit_windows: hit_windows(overall_difficulty, mode),
+            mode,
+        }
+    }
+
+    /// The time in milliseconds that the player experiences when the map is played at the rate.
+    pub fn effective(&self, ms: f64) -> f64{
+        round_dec(ms / self.rate, 2)
+    }
+
+    /// The approach rate that feels like the approach rate of the map played at the rate. This can be above 10.
+    pub fn effective_ar(&self) -> Option<f64>{
+        self.preempt.map(|x| round_dec(ar_for_preempt(x / self.rate), 2))
+    }
+
+    /// The overall difficulty that feels like the overall difficulty of the map played at the rate.
+    pub fn effective_od(&self) -> Option<f64>{
+        self.hit_windows.first().and_then(|(_, window)| od_for_window(window / self.rate, self.mode)).map(|x| round_dec(x, 2))
+    }
+}
+
+/// Time in milliseconds an object is visible before it has to be hit.
+pub fn preempt(ar: f64) -> f64{
+    if ar <= 5.0 { 1800.0 - 120.0 * ar } else { 1950.0 - 150.0 * ar }
+}
+
+/// Approach rate with the given preempt in milliseconds.
+pub fn ar_for_preempt(ms: f64) -> f64{
+    if ms > 1200.0 { (1800.0 - ms) / 120.0 } else { (1950.0 - ms) / 150.0 }
+}
+
+/// Time in milliseconds an osu!standard object takes to fade in.
+pub fn fade_in(ar: f64) -> f64{
+    if ar <= 5.0 { 1200.0 - 80.0 * ar } else { 1300.0 - 100.0 * ar }
+}
+
+/// Hit windows in milliseconds for the overall difficulty, from the best judgement to the worst.
+/// osu!catch has no hit windows.
+pub fn hit_windows(od: f64, mode: Mode) -> Vec<(&'static str, f64)>{
+    match mode{
+        Mode::Osu => vec![("300", 80.0 - 6.0 * od), ("100", 140.0 - 8.0 * od), ("50", 200.0 - 10.0 * od)],
+        Mode::Taiko => vec![("Great", 50.0 - 3.0 * od), ("Ok", if od <= 5.0 { 120.0 - 8.0 * od } else { 110.0 - 6.0 * od })],
+        Mode::Mania => vec![
+            ("300", 64.0 - 3.0 * od),
+            ("200", 97.0 - 3.0 * od),
+            ("100", 127.0 - 3.0 * od),
+            ("50", 151.0 - 3.0 * od),
+        ],
+        Mode::Catch => Vec::new(),
+    }
+}
+
+/// Overall difficulty with the given window in milliseconds for the best judgement.
+/// osu!catch has no hit windows, so there is no such overall difficulty.
+pub fn od_for_window(ms: f64, mode: Mode) -> Option<f64>{
+    match mode{
+        Mode::Osu => Some((80.0 - ms) / 6.0),
+        Mode::Taiko => Some((50.0 - ms) / 3.0),
+        Mode::Mania => Some((64.0 - ms) / 3.0),
+        Mode::Catch => None,
+    }
+}
+
+/// Difficulty values that can be given as the player experiences them at the rate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimedValue{
+    ApproachRate,
+    /// The mode decides the hit windows of the overall difficulty
+    OverallDifficulty(Mode),
+}
+
+impl TimedValue{
+    /// Preempt or window of the best judgement in milliseconds for the value.
+    pub fn to_ms(&self, value: f64) -> Option<f64>{
+        match self{
+            TimedValue::ApproachRate => Some(preempt(value)),
+            TimedValue::OverallDifficulty(mode) => hit_windows(value, *mode).first().map(|x| x.1),
+        }
+    }
+
+    /// Value with the given preempt or window of the best judgement in milliseconds.
+    pub fn from_ms(&self, ms: f64) -> Option<f64>{
+        match self{
+            TimedValue::ApproachRate => Some(ar_for_preempt(ms)),
+            TimedValue::OverallDifficulty(mode) => od_for_window(ms, *mode),
+        }
+    }
+
+    /// The value in the map as the player experiences it at the rate, given as the value and in milliseconds.
+    pub fn effective(&self, value: f64, rate: f64) -> Option<(f64, f64)>{
+        let ms = self.to_ms(value)? / rate;
+        Some((round_dec(self.from_ms(ms)?, 2), round_dec(ms, 2)))
+    }
+
+    /// The value in the map that results in the target when the map is played at the rate.
+    /// The value is not clamped, so it can be outside of what osu! accepts.
+    pub fn map_value(&self, target: DifficultyTarget, rate: f64) -> Option<f64>{
+        let ms = match target{
+            DifficultyTarget::Value(value) => return Some(value),
+            DifficultyTarget::Effective(value) => self.to_ms(value)?,
+            DifficultyTarget::Milliseconds(ms) => ms,
+        };
+        self.from_ms(ms * rate)
+    }
+}
+
+/// A difficulty value as written in the map, as the value the player experiences at the rate,
+/// or as the preempt or window of the best judgement in milliseconds the player experiences at the rate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DifficultyTarget{
+    Value(f64),
+    Effective(f64),
+    Milliseconds(f64),
+}
+
+impl DifficultyTarget{
+    /// Target of the same kind with a different value.
+    pub fn with_value(&self, value: f64) -> Self{
+        match self{
+            DifficultyTarget::Value(_) => DifficultyTarget::Value(value),
+            DifficultyTarget::Effective(_) => DifficultyTarget::Effective(value),
+            DifficultyTarget::Milliseconds(_) => DifficultyTarget::Milliseconds(value),
+        }
+    }
+}
+
+impl fmt::Display for DifficultyTarget{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
+        match self{
+            DifficultyTarget::Value(value) => write!(f, "{}", value),
+            DifficultyTarget::Effective(value) => write!(f, "@{}", value),
+            DifficultyTarget::Milliseconds(ms) => write!(f, "{}ms", ms),
+        }
+    }
+}
+
+impl FromStr for DifficultyTarget{
+    type Err = anyhow::Error;
+
+    /// Parses '9.3' as a value in the map, '@10.3' as an effective value and '400ms' as milliseconds
+    fn from_str(s: &str) -> Result<Self>{
+        let s = s.trim();
+        let (target, value): (fn(f64) -> Self, &str) = if let Some(value) = s.strip_prefix('@'){
+            (DifficultyTarget::Effective, value)
+        }else if let Some(value) = s.strip_suffix("ms"){
+            (DifficultyTarget::Milliseconds, value)
+        }else{
+            (DifficultyTarget::Value, s)
+        };
+        match value.trim().parse::<f64>(){
+            Ok(k) if k.is_finite() => Ok(target(k)),
+            _ => Err(anyhow!("Invalid difficulty value: '{}'. Use a value such as '9', an effective value such as '@10.3' or milliseconds such as '450ms'.", s))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const MODES: [Mode; 3] = [Mode::Osu, Mode::Taiko, Mode::Mania];
+
+    fn assert_close(a: f64, b: f64){
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn parses_difficulty_targets(){
+        assert_eq!("9".parse::<DifficultyTarget>().unwrap(), DifficultyTarget::Value(9.0));
+        assert_eq!("@10.3".parse::<DifficultyTarget>().unwrap(), DifficultyTarget::Effective(10.3));
+        assert_eq!("450ms".parse::<DifficultyTarget>().unwrap(), DifficultyTarget::Milliseconds(450.0));
+        assert_eq!(" @ 8 ".parse::<DifficultyTarget>().unwrap(), DifficultyTarget::Effective(8.0));
+        assert_eq!("22.5 ms".parse::<DifficultyTarget>().unwrap(), DifficultyTarget::Milliseconds(22.5));
+    }
+
+    #[test]
+    fn rejects_invalid_difficulty_targets(){
+        for target in ["", "@", "ms", "abc", "@abc", "10ms@", "inf", "NaN"]{
+            assert!(target.parse::<DifficultyTarget>().is_err(), "'{}' was parsed", target);
+        }
+    }
+
+    #[test]
+    fn difficulty_targets_display_as_they_are_parsed(){
+        for target in [DifficultyTarget::Value(9.3), DifficultyTarget::Effective(10.3), DifficultyTarget::Milliseconds(450.0)]{
+            assert_eq!(target.to_string().parse::<DifficultyTarget>().unwrap(), target);
+        }
+    }
+
+    #[test]
+    fn preempt_round_trips(){
+        for ar in (0..=20).map(|x| x as f64 / 2.0){
+            assert_close(ar_for_preempt(preempt(ar)), ar);
+        }
+        // Approach rates above 10 can be reached with rates
+        assert_close(ar_for_preempt(300.0), 11.0);
+    }
+
+    #[test]
+    fn hit_window_round_trips(){
+        for mode in MODES{
+            for od in (0..=20).map(|x| x as f64 / 2.0){
+                let (_, window) = hit_windows(od, mode)[0];
+                assert_close(od_for_window(window, mode).unwrap(), od);
+            }
+        }
+        assert!(hit_windows(5.0, Mode::Catch).is_empty());
+        assert_eq!(od_for_window(50.0, Mode::Catch), None);
+    }
+
+    #[test]
+    fn effective_targets_round_trip(){
+        let values = [TimedValue::ApproachRate, TimedValue::OverallDifficulty(Mode::Osu), TimedValue::OverallDifficulty(Mode::Taiko), TimedValue::OverallDifficulty(Mode::Mania)];
+        for value in values{
+            for rate in [0.75, 1.0, 1.5]{
+                let map_value = value.map_value(DifficultyTarget::Effective(8.0), rate).unwrap();
+                assert_close(value.effective(map_value, rate).unwrap().0, 8.0);
+
+                let ms = value.to_ms(8.0).unwrap();
+                let map_value = value.map_value(DifficultyTarget::Milliseconds(ms), rate).unwrap();
+                assert_close(value.effective(map_value, rate).unwrap().1, round_dec(ms, 2));
+            }
+        }
+    }
+}
@@ -0,0 +1,785 @@
+use anyhow::{Result, anyhow};
+use libosu::prelude::*;
+use crate::structs::{Mirror, Spinners};
+
+/// Width of the osu!standard playfield in osu!pixels.
+pub const PLAYFIELD_WIDTH: i32 = 512;
+/// Height of the osu!standard playfield in osu!pixels.
+pub const PLAYFIELD_HEIGHT: i32 = 384;
+
+/// Converts an osu!standard map to the given mode the same way osu! does when playing converted maps.
+/// Catch maps use the same hit objects as osu!standard maps, so only taiko and mania maps need their hit objects converted.
+/// The rosu-pp beatmap of the original map is needed since the mania conversion is done by rosu-pp.
+pub fn convert_mode(map_data: &mut Beatmap, mode: Mode, rosu_map: &rosu_pp::Beatmap) -> Result<()>{
+    if map_data.mode == mode{
+        return Ok(())
+    }else if map_data.mode != Mode::Osu{
+        return Err(anyhow!("Only osu!standard maps can be converted to other modes."));
+    }
+    match mode{
+        Mode::Osu | Mode::Catch => (),
+        Mode::Taiko => convert_sliders_to_taiko_hits(map_data),
+        Mode::Mania => {
+            let converted = rosu_map.convert_mode(rosu_pp::GameMode::Mania);
+            let keys = converted.cs.round() as u32;
+            let original = std::mem::take(&mut map_data.hit_objects);
+            map_data.hit_objects = converted.hit_objects.iter().filter_map(|h| {
+                let start_time = h.start_time.round() as i32;
+
+                // Use the last original object before the converted one as a template to keep its hitsounds
+                let ind = original.partition_point(|x| x.start_time.0 <= start_time);
+                let mut new = original.get(ind.saturating_sub(1))?.clone();
+                new.start_time.0 = start_time;
+                new.pos.x = mania_x(mania_column(h.pos.x.round() as i32, keys), keys);
+                new.pos.y = PLAYFIELD_HEIGHT / 2;
+                new.new_combo = false;
+                new.kind = match h.kind{
+                    rosu_pp::parse::HitObjectKind::Hold{ end_time } => HitObjectKind::Hold(HoldInfo{ end_time: Millis(end_time.round() as i32) }),
+                    _ => HitObjectKind::Circle
+                };
+                Some(new)
+            }).collect();
+            map_data.difficulty.circle_size = keys as f64;
+        }
+    }
+    map_data.mode = mode;
+    Ok(())
+}
+
+// Logic taken from osu!lazer's TaikoBeatmapConverter
+/// Turns short sliders into a series of hits like osu! does when converting osu!standard maps to taiko.
+/// The remaining sliders become drum rolls.
+fn convert_sliders_to_taiko_hits(map_data: &mut Beatmap){
+    const LEGACY_VELOCITY_MULTIPLIER: f64 = 1.4;
+    let mut hit_objects = Vec::with_capacity(map_data.hit_objects.len());
+    for h in std::mem::take(&mut map_data.hit_objects){
+        let slider = match &h.kind{
+            HitObjectKind::Slider(slider) => slider,
+            _ => {
+                hit_objects.push(h);
+                continue
+            }
+        };
+        let (mpb, slider_velocity) = timing_at(&map_data.timing_points, h.start_time.0);
+        let beat_length = mpb / slider_velocity;
+        let spans = slider.num_repeats.max(1) as f64;
+        let distance = slider.pixel_length * spans * LEGACY_VELOCITY_MULTIPLIER;
+        let taiko_velocity = 100.0 * map_data.difficulty.slider_multiplier * LEGACY_VELOCITY_MULTIPLIER;
+        let taiko_duration = distance / taiko_velocity * beat_length;
+        let osu_velocity = taiko_velocity * 1000.0 / beat_length;
+        let tick_spacing = (beat_length / map_data.difficulty.slider_tick_rate).min(taiko_duration / spans);
+
+        if tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_length{
+            let mut time = h.start_time.0 as f64;
+            let mut node: usize = 0;
+            while time <= h.start_time.0 as f64 + taiko_duration + tick_spacing / 8.0{
+                let mut hit = h.clone();
+                hit.start_time.0 = time.round() as i32;
+                hit.new_combo = h.new_combo && node == 0;
+                if !slider.edge_additions.is_empty(){
+                    hit.additions = slider.edge_additions[node % slider.edge_additions.len()].clone();
+                }
+                hit.kind = HitObjectKind::Circle;
+                hit_objects.push(hit);
+                time += tick_spacing;
+                node += 1;
+            }
+        }else{
+            hit_objects.push(h);
+        }
+    }
+    map_data.hit_objects = hit_objects;
+}
+
+/// Flips every hit object and slider control point vertically, like the HR mod does.
+pub fn flip_vertical(map_data: &mut Beatmap){
+    map_positions(map_data, |x, y| (x, PLAYFIELD_HEIGHT as f64 - y));
+}
+
+/// Mirrors the map along the given axis.
+pub fn mirror(map_data: &mut Beatmap, mirror: Mirror){
+    let (width, height) = (PLAYFIELD_WIDTH as f64, PLAYFIELD_HEIGHT as f64);
+    match mirror{
+        Mirror::None => (),
+        Mirror::Horizontal => map_positions(map_data, |x, y| (width - x, y)),
+        Mirror::Vertical => map_positions(map_data, |x, y| (x, height - y)),
+        Mirror::Diagonal => map_positions(map_data, |x, y| (width - x, height - y)),
+    }
+}
+
+/// Rotates the map clockwise around the center of the playfield by the given amount of degrees.
+/// Objects that end up outside of the playfield are moved back inside of it.
+pub fn rotate(map_data: &mut Beatmap, degrees: f64){
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (center_x, center_y) = (PLAYFIELD_WIDTH as f64 / 2.0, PLAYFIELD_HEIGHT as f64 / 2.0);
+    map_positions(map_data, |x, y| {
+        let (dx, dy) = (x - center_x, y - center_y);
+        (center_x + dx * cos - dy * sin, center_y + dx * sin + dy * cos)
+    });
+    clamp_to_playfield(map_data);
+}
+
+/// Multiplies the distance between consecutive hit objects by the given factor.
+/// Sliders keep their shape and objects that would leave the playfield are clamped to its edges.
+pub fn scale_spacing(map_data: &mut Beatmap, factor: f64){
+    let mut previous: Option<((i32, i32), (i32, i32))> = None;
+    for h in &mut map_data.hit_objects{
+        let original = (h.pos.x, h.pos.y);
+
+        // Spinners are always in the center so they don't affect spacing
+        if let HitObjectKind::Spinner(_) = h.kind{
+            continue
+        }
+        if let Some((prev_original, prev_new)) = previous{
+            let x = prev_new.0 as f64 + (original.0 - prev_original.0) as f64 * factor;
+            let y = prev_new.1 as f64 + (original.1 - prev_original.1) as f64 * factor;
+            translate(h, x.round() as i32 - h.pos.x, y.round() as i32 - h.pos.y);
+            clamp_object(h);
+        }
+        previous = Some((original, (h.pos.x, h.pos.y)));
+    }
+}
+
+/// Converts every slider into a circle at the slider head.
+/// If tail_circles is true, an extra circle is added where the slider ends.
+pub fn sliders_to_circles(map_data: &mut Beatmap, tail_circles: bool){
+    let mut tails = Vec::new();
+    for ind in 0..map_data.hit_objects.len(){
+        let end = match &map_data.hit_objects[ind].kind{
+            HitObjectKind::Slider(slider) => {
+                let h = &map_data.hit_objects[ind];
+                let (beat_length, slider_velocity) = timing_at(&map_data.timing_points, h.start_time.0);
+                let duration = slider.pixel_length / (map_data.difficulty.slider_multiplier * 100.0 * slider_velocity) * beat_length * slider.num_repeats as f64;
+
+                // The slider ends at its head if it has an even number of slides
+                let end_pos = if slider.num_repeats % 2 == 1{
+                    let (x, y) = slider_end(h, slider);
+                    (x.round() as i32, y.round() as i32)
+                }else{
+                    (h.pos.x, h.pos.y)
+                };
+                Some((h.start_time.0 + duration.round() as i32, end_pos))
+            },
+            _ => None
+        };
+        if let Some((end_time, end_pos)) = end{
+            let h = &mut map_data.hit_objects[ind];
+            h.kind = HitObjectKind::Circle;
+            if tail_circles{
+                let mut tail = h.clone();
+                tail.start_time.0 = end_time;
+                tail.pos.x = end_pos.0;
+                tail.pos.y = end_pos.1;
+                tail.new_combo = false;
+                tails.push(tail);
+            }
+        }
+    }
+    if !tails.is_empty(){
+        map_data.hit_objects.append(&mut tails);
+        map_data.hit_objects.sort_by_key(|h| h.start_time.0);
+    }
+}
+
+/// Removes every hit object that does not start within the given range in milliseconds.
+pub fn practice_range(map_data: &mut Beatmap, start: i32, end: i32){
+    map_data.hit_objects.retain(|h| (start..=end).contains(&h.start_time.0));
+}
+
+/// Removes or shortens every spinner in the map.
+pub fn adjust_spinners(map_data: &mut Beatmap, spinners: Spinners){
+    match spinners{
+        Spinners::Keep => (),
+        Spinners::Remove => {
+            let remove = map_data.hit_objects.iter().map(|h| matches!(h.kind, HitObjectKind::Spinner(_))).collect::<Vec<bool>>();
+            remove_objects(map_data, &remove);
+        },
+        Spinners::Shorten(max_length) => {
+            for h in &mut map_data.hit_objects{
+                let start_time = h.start_time.0;
+                if let HitObjectKind::Spinner(spinner) = &mut h.kind{
+                    spinner.end_time.0 = spinner.end_time.0.min(start_time + max_length as i32);
+                }
+            }
+        }
+    }
+}
+
+/// Removes every other circle from streams that are snapped to 1/divisor of a beat or faster.
+pub fn thin_streams(map_data: &mut Beatmap, divisor: u32){
+    let mut remove = vec![false; map_data.hit_objects.len()];
+    let mut previous: Option<i32> = None;
+    let mut stream_length: usize = 0;
+    for (ind, h) in map_data.hit_objects.iter().enumerate(){
+        let is_circle = matches!(h.kind, HitObjectKind::Circle);
+        match previous{
+            Some(prev) if is_circle => {
+                let (beat_length, _) = timing_at(&map_data.timing_points, h.start_time.0);
+                // Allow for a few milliseconds of error since times are rounded
+                if ((h.start_time.0 - prev) as f64) <= beat_length / divisor as f64 + 2.0{
+                    stream_length += 1;
+                    remove[ind] = stream_length % 2 == 1;
+                }else{
+                    stream_length = 0;
+                }
+            },
+            _ => stream_length = 0
+        }
+        previous = if is_circle { Some(h.start_time.0) } else { None };
+    }
+    remove_objects(map_data, &remove);
+}
+
+/// Returns the osu!mania column of the given x position.
+pub fn mania_column(x: i32, keys: u32) -> u32{
+    ((x.max(0) as f64 * keys as f64 / PLAYFIELD_WIDTH as f64).floor() as u32).min(keys.max(1) - 1)
+}
+
+/// Returns the x position of the given osu!mania column.
+pub fn mania_x(column: u32, keys: u32) -> i32{
+    ((column as f64 + 0.5) * PLAYFIELD_WIDTH as f64 / keys as f64).floor() as i32
+}
+
+/// Mirrors the columns of an osu!mania map.
+pub fn mirror_columns(map_data: &mut Beatmap, keys: u32){
+    for h in &mut map_data.hit_objects{
+        h.pos.x = mania_x(keys - 1 - mania_column(h.pos.x, keys), keys);
+    }
+}
+
+/// Randomly swaps the columns of an osu!mania map. The same seed always produces the same map.
+pub fn shuffle_columns(map_data: &mut Beatmap, keys: u32, seed: u64){
+    let mut rng = Rng::new(seed);
+    let mut columns = (0..keys).collect::<Vec<u32>>();
+    for i in (1..columns.len()).rev(){
+        columns.swap(i, (rng.next_u64() % (i as u64 + 1)) as usize);
+    }
+    for h in &mut map_data.hit_objects{
+        h.pos.x = mania_x(columns[mania_column(h.pos.x, keys) as usize], keys);
+    }
+}
+
+/// Converts an osu!mania map to a different key count.
+/// Columns are spread evenly over the new key count. When notes of a chord or overlapping long notes end up
+/// in the same column they are moved to the nearest free column, or removed if there is none.
+pub fn convert_key_count(map_data: &mut Beatmap, from: u32, to: u32){
+    if from == to || from == 0 || to == 0{
+        return
+    }
+    let mut busy_until = vec![i32::MIN; to as usize];
+    let mut remove = vec![false; map_data.hit_objects.len()];
+    for (ind, h) in map_data.hit_objects.iter_mut().enumerate(){
+        let column = mania_column(h.pos.x, from);
+        let target = if from == 1{
+            0
+        }else{
+            (column as f64 * (to - 1) as f64 / (from - 1) as f64).round() as i64
+        };
+        let end_time = match &h.kind{
+            HitObjectKind::Hold(k) => k.end_time.0,
+            _ => h.start_time.0
+        };
+
+        // Look for the closest free column, alternating between the left and right side
+        let free = (0..to as i64).flat_map(|offset| [target - offset, target + offset])
+            .find(|&x| x >= 0 && x < to as i64 && busy_until[x as usize] < h.start_time.0);
+        match free{
+            Some(x) => {
+                busy_until[x as usize] = end_time;
+                h.pos.x = mania_x(x as u32, to);
+            },
+            None => remove[ind] = true
+        }
+    }
+    remove_objects(map_data, &remove);
+    map_data.difficulty.circle_size = to as f64;
+}
+
+/// Turns every note of an osu!mania map into a long note that ends at the next note in its column.
+/// The long notes end 1/gap_divisor of a beat before the next note, but are never shortened by more than half.
+pub fn full_long_notes(map_data: &mut Beatmap, keys: u32, gap_divisor: u32){
+    for column in column_indices(map_data, keys){
+        for pair in column.windows(2){
+            let start = map_data.hit_objects[pair[0]].start_time.0;
+            let next_start = map_data.hit_objects[pair[1]].start_time.0;
+            let end_time = start + long_note_duration(&map_data.timing_points, start, next_start, gap_divisor);
+            map_data.hit_objects[pair[0]].kind = HitObjectKind::Hold(HoldInfo{ end_time: Millis(end_time) });
+        }
+    }
+}
+
+/// Turns every long note of an osu!mania map into a regular note.
+pub fn remove_long_notes(map_data: &mut Beatmap){
+    for h in &mut map_data.hit_objects{
+        if let HitObjectKind::Hold(_) = h.kind{
+            h.kind = HitObjectKind::Circle;
+        }
+    }
+}
+
+/// Inverts the long notes of an osu!mania map, turning the gaps between notes into long notes and vice versa.
+/// This works like the Invert mod in osu!lazer, except that the last note of each column is kept as a regular note
+/// instead of being dropped, since it has no next note for a long note to run to.
+pub fn invert_long_notes(map_data: &mut Beatmap, keys: u32, gap_divisor: u32){
+    let mut inverted = Vec::with_capacity(map_data.hit_objects.len());
+    for column in column_indices(map_data, keys){
+        let mut locations = Vec::with_capacity(column.len() * 2);
+        for ind in column{
+            let h = &map_data.hit_objects[ind];
+            locations.push((h.start_time.0, ind));
+            if let HitObjectKind::Hold(k) = &h.kind{
+                locations.push((k.end_time.0, ind));
+            }
+        }
+        locations.sort_by_key(|x| x.0);
+
+        // Every location becomes a long note that runs until the next location in the column
+        for pair in locations.windows(2){
+            let ((start, ind), (end, _)) = (pair[0], pair[1]);
+            let mut h = map_data.hit_objects[ind].clone();
+            h.start_time.0 = start;
+            h.kind = HitObjectKind::Hold(HoldInfo{ end_time: Millis(start + long_note_duration(&map_data.timing_points, start, end, gap_divisor)) });
+            inverted.push(h);
+        }
+        // The last location has no next location to run to, so it is kept as a regular note
+        if let Some(&(start, ind)) = locations.last(){
+            let mut h = map_data.hit_objects[ind].clone();
+            h.start_time.0 = start;
+            h.kind = HitObjectKind::Circle;
+            inverted.push(h);
+        }
+    }
+    inverted.sort_by_key(|h| h.start_time.0);
+    map_data.hit_objects = inverted;
+}
+
+/// Calculates the duration of a long note from start to end, leaving a gap of 1/gap_divisor of a beat.
+fn long_note_duration(points: &[TimingPoint], start: i32, end: i32, gap_divisor: u32) -> i32{
+    let (beat_length, _) = timing_at(points, end);
+    let duration = (end - start) as f64;
+    (duration / 2.0).max(duration - beat_length / gap_divisor as f64).round() as i32
+}
+
+/// Returns the indices of the hit objects in each column of an osu!mania map.
+fn column_indices(map_data: &Beatmap, keys: u32) -> Vec<Vec<usize>>{
+    let mut columns = vec![Vec::new(); keys as usize];
+    for (ind, h) in map_data.hit_objects.iter().enumerate(){
+        columns[mania_column(h.pos.x, keys) as usize].push(ind);
+    }
+    columns
+}
+
+/// Returns the beat length and slider velocity multiplier at the given time.
+fn timing_at(points: &[TimingPoint], time: i32) -> (f64, f64){
+    // Objects before the first uninherited timing point use its beat length
+    let mut beat_length = points.iter().find_map(|x| match &x.kind{
+        TimingPointKind::Uninherited(k) => Some(k.mpb),
+        _ => None
+    }).unwrap_or(500.0);
+    let mut slider_velocity = 1.0;
+    for point in points.iter().take_while(|x| x.time.0 <= time){
+        match &point.kind{
+            TimingPointKind::Uninherited(k) => {
+                beat_length = k.mpb;
+                slider_velocity = 1.0;
+            },
+            TimingPointKind::Inherited(k) => slider_velocity = k.slider_velocity,
+        }
+    }
+    (beat_length, slider_velocity)
+}
+
+/// Amount of points each curve of a slider path is approximated with
+const CURVE_STEPS: usize = 50;
+
+// Logic taken from osu!lazer's SliderPath and PathApproximator
+/// Position a slider reaches at the end of its first slide, found by following its path for its pixel length.
+/// Paths that are shorter than the pixel length are extended in the direction of their last segment like osu! does.
+fn slider_end(h: &HitObject, slider: &SliderInfo) -> (f64, f64){
+    let head = (h.pos.x as f64, h.pos.y as f64);
+    // The head is the first point of the path, whether or not the control points repeat it
+    let skip = slider.control_points.first().is_some_and(|x| x.x == h.pos.x && x.y == h.pos.y) as usize;
+    let points = std::iter::once(head)
+        .chain(slider.control_points.iter().skip(skip).map(|x| (x.x as f64, x.y as f64)))
+        .collect::<Vec<(f64, f64)>>();
+    let path = match slider.kind{
+        SliderSplineKind::Linear => points,
+        SliderSplineKind::Perfect => circular_arc_path(&points).unwrap_or_else(|| bezier_path(&points)),
+        SliderSplineKind::Bezier => bezier_path(&points),
+        SliderSplineKind::Catmull => catmull_path(&points),
+    };
+    point_at_length(&path, slider.pixel_length)
+}
+
+/// Approximates a bezier path, which starts a new curve at every point that is repeated.
+fn bezier_path(points: &[(f64, f64)]) -> Vec<(f64, f64)>{
+    let mut path = Vec::new();
+    let mut start = 0;
+    for end in 1..=points.len(){
+        if end < points.len() && points[end] != points[end - 1]{
+            continue
+        }
+        let curve = &points[start..end];
+        if curve.len() > 2{
+            path.extend((0..=CURVE_STEPS).map(|x| bezier_point(curve, x as f64 / CURVE_STEPS as f64)));
+        }else{
+            path.extend_from_slice(curve);
+        }
+        start = end;
+    }
+    path
+}
+
+/// Point of a bezier curve at t between 0 and 1, using De Casteljau's algorithm.
+fn bezier_point(curve: &[(f64, f64)], t: f64) -> (f64, f64){
+    let mut points = curve.to_vec();
+    for len in (1..points.len()).rev(){
+        for ind in 0..len{
+            points[ind] = (points[ind].0 + (points[ind + 1].0 - points[ind].0) * t, points[ind].1 + (points[ind + 1].1 - points[ind].1) * t);
+        }
+    }
+    points[0]
+}
+
+/// Approximates the arc through three points. Returns None if the points are on a line.
+fn circular_arc_path(points: &[(f64, f64)]) -> Option<Vec<(f64, f64)>>{
+    let &[a, b, c] = points else {
+        return None
+    };
+    let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+    if d.abs() < 1e-3{
+        return None
+    }
+    let (a_sq, b_sq, c_sq) = (a.0 * a.0 + a.1 * a.1, b.0 * b.0 + b.1 * b.1, c.0 * c.0 + c.1 * c.1);
+    let center = (
+        (a_sq * (b.1 - c.1) + b_sq * (c.1 - a.1) + c_sq * (a.1 - b.1)) / d,
+        (a_sq * (c.0 - b.0) + b_sq * (a.0 - c.0) + c_sq * (b.0 - a.0)) / d,
+    );
+    let radius = (a.0 - center.0).hypot(a.1 - center.1);
+    let theta_start = (a.1 - center.1).atan2(a.0 - center.0);
+    let mut theta_end = (c.1 - center.1).atan2(c.0 - center.0);
+    while theta_end < theta_start{
+        theta_end += 2.0 * std::f64::consts::PI;
+    }
+    let (mut direction, mut theta_range) = (1.0, theta_end - theta_start);
+    // The arc goes the other way around if the middle point is on the other side of the line from a to c
+    if (c.1 - a.1) * (b.0 - a.0) - (c.0 - a.0) * (b.1 - a.1) < 0.0{
+        direction = -1.0;
+        theta_range = 2.0 * std::f64::consts::PI - theta_range;
+    }
+    Some((0..=CURVE_STEPS).map(|x|{
+        let theta = theta_start + direction * theta_range * x as f64 / CURVE_STEPS as f64;
+        (center.0 + radius * theta.cos(), center.1 + radius * theta.sin())
+    }).collect())
+}
+
+/// Approximates a catmull-rom path through all of the points.
+fn catmull_path(points: &[(f64, f64)]) -> Vec<(f64, f64)>{
+    let mut path = Vec::new();
+    for ind in 0..points.len().saturating_sub(1){
+        let v2 = points[ind];
+        let v3 = points[ind + 1];
+        let v1 = if ind > 0 { points[ind - 1] } else { v2 };
+        let v4 = points.get(ind + 2).copied().unwrap_or((2.0 * v3.0 - v2.0, 2.0 * v3.1 - v2.1));
+        path.extend((0..=CURVE_STEPS).map(|x|{
+            let t = x as f64 / CURVE_STEPS as f64;
+            let point = |p1: f64, p2: f64, p3: f64, p4: f64| 0.5 * (2.0 * p2 + (-p1 + p3) * t
+                + (2.0 * p1 - 5.0 * p2 + 4.0 * p3 - p4) * t * t
+                + (-p1 + 3.0 * p2 - 3.0 * p3 + p4) * t * t * t);
+            (point(v1.0, v2.0, v3.0, v4.0), point(v1.1, v2.1, v3.1, v4.1))
+        }));
+    }
+    if path.is_empty(){
+        path.extend_from_slice(points);
+    }
+    path
+}
+
+/// Point at the given distance along an approximated path.
+fn point_at_length(path: &[(f64, f64)], length: f64) -> (f64, f64){
+    let lerp = |start: (f64, f64), end: (f64, f64), t: f64| (start.0 + (end.0 - start.0) * t, start.1 + (end.1 - start.1) * t);
+    let mut remaining = length;
+    let mut last = None;
+    for pair in path.windows(2){
+        let (start, end) = (pair[0], pair[1]);
+        let distance = (end.0 - start.0).hypot(end.1 - start.1);
+        if distance == 0.0{
+            continue
+        }
+        if remaining <= distance{
+            return lerp(start, end, remaining / distance);
+        }
+        remaining -= distance;
+        last = Some((start, end, distance));
+    }
+    match last{
+        Some((start, end, distance)) => lerp(start, end, 1.0 + remaining / distance),
+        None => path.first().copied().unwrap_or_default(),
+    }
+}
+
+/// Removes the hit objects marked in the given slice.
+/// New combos of removed objects are moved to the next remaining object to keep the combo colours intact.
+fn remove_objects(map_data: &mut Beatmap, remove: &[bool]){
+    let mut carry_combo = false;
+    let hit_objects = std::mem::take(&mut map_data.hit_objects);
+    map_data.hit_objects = hit_objects.into_iter().zip(remove).filter_map(|(mut h, &remove)| {
+        if remove{
+            carry_combo |= h.new_combo;
+            None
+        }else{
+            h.new_combo |= carry_combo;
+            carry_combo = false;
+            Some(h)
+        }
+    }).collect();
+}
+
+/// Randomly changes the angles between hit objects while keeping the distance and rhythm between them.
+/// The same seed always produces the same map.
+pub struct Randomizer{
+    rng: Rng,
+    angle: f64,
+    previous: Option<((i32, i32), (i32, i32))>,
+}
+
+impl Randomizer{
+    pub fn new(seed: u64) -> Self{
+        let mut rng = Rng::new(seed);
+        let angle = rng.next_f64() * std::f64::consts::TAU;
+        Randomizer{
+            rng,
+            angle,
+            previous: None,
+        }
+    }
+
+    /// Moves the hit object to a random angle from the previous one.
+    /// Must be called on every hit object in order.
+    pub fn apply(&mut self, h: &mut HitObject){
+        // Spinners are always in the center so they are skipped
+        if let HitObjectKind::Spinner(_) = h.kind{
+            return
+        }
+        let original = (h.pos.x, h.pos.y);
+        if let Some((prev_original, prev_new)) = self.previous{
+            let (dx, dy) = ((original.0 - prev_original.0) as f64, (original.1 - prev_original.1) as f64);
+            let distance = dx.hypot(dy);
+
+            // Keep stacks stacked
+            if distance > 0.0{
+                self.angle += (self.rng.next_f64() * 2.0 - 1.0) * std::f64::consts::PI;
+                let mut x = prev_new.0 as f64 + distance * self.angle.cos();
+                let mut y = prev_new.1 as f64 + distance * self.angle.sin();
+
+                // Jump in the opposite direction if the object would leave the playfield
+                if x < 0.0 || x > PLAYFIELD_WIDTH as f64 || y < 0.0 || y > PLAYFIELD_HEIGHT as f64{
+                    self.angle += std::f64::consts::PI;
+                    x = prev_new.0 as f64 + distance * self.angle.cos();
+                    y = prev_new.1 as f64 + distance * self.angle.sin();
+                }
+                translate(h, x.round() as i32 - h.pos.x, y.round() as i32 - h.pos.y);
+            }else{
+                translate(h, prev_new.0 - h.pos.x, prev_new.1 - h.pos.y);
+            }
+        }
+        clamp_object(h);
+        self.previous = Some((original, (h.pos.x, h.pos.y)));
+    }
+}
+
+/// Small seedable pseudo random number generator (xorshift64*) used by the randomizing transforms
+/// so that the output only depends on the seed.
+pub struct Rng(u64);
+
+impl Rng{
+    pub fn new(seed: u64) -> Self{
+        // Mix the seed with splitmix64 since xorshift does not work with a state of 0
+        let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        Rng((z ^ (z >> 31)).max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64{
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    /// Returns a random float in the range [0, 1).
+    pub fn next_f64(&mut self) -> f64{
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Applies the given function to the position of every hit object and slider control point.
+pub fn map_positions(map_data: &mut Beatmap, f: impl Fn(f64, f64) -> (f64, f64)){
+    for h in &mut map_data.hit_objects{
+        let (x, y) = f(h.pos.x as f64, h.pos.y as f64);
+        h.pos.x = x.round() as i32;
+        h.pos.y = y.round() as i32;
+        if let HitObjectKind::Slider(slider) = &mut h.kind{
+            for point in &mut slider.control_points{
+                let (x, y) = f(point.x as f64, point.y as f64);
+                point.x = x.round() as i32;
+                point.y = y.round() as i32;
+            }
+        }
+    }
+}
+
+/// Moves every hit object that is partially outside of the playfield back inside of it.
+pub fn clamp_to_playfield(map_data: &mut Beatmap){
+    for h in &mut map_data.hit_objects{
+        clamp_object(h);
+    }
+}
+
+/// Beat snap divisors that times are snapped to when rescaling them, from coarse to fine
+const SNAP_DIVISORS: [f64; 9] = [1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 32.0];
+
+/// The uninherited timing points of a map with their exact offsets, used to rescale times along the beat grid.
+pub struct BeatGrid{
+    points: Vec<(f64, f64)>,
+}
+
+impl BeatGrid{
+    /// Creates the beat grid from the timing points of a map and their exact offsets.
+    /// Timing points without an exact offset use their rounded time.
+    pub fn new(points: &[TimingPoint], offsets: &[f64]) -> Self{
+        let points = points.iter().enumerate().filter_map(|(ind, point)| match &point.kind{
+            TimingPointKind::Uninherited(k) => Some((offsets.get(ind).copied().unwrap_or(point.time.0 as f64), k.mpb)),
+            _ => None
+        }).collect();
+        BeatGrid{ points }
+    }
+
+    /// Scales a time to the given rate through its position on the beat grid.
+    /// Times within a millisecond of a snap divisor are treated as exactly snapped, so the rounding of
+    /// the original map does not add up with the rounding of the scaled time.
+    pub fn scale(&self, time: i32, rate: f64) -> f64{
+        let time = time as f64;
+        // Objects can be slightly before their timing point due to rounding
+        let Some(&(offset, beat_length)) = self.points.iter().rev().find(|(offset, _)| *offset <= time + 1.0).or(self.points.first()) else {
+            return time / rate;
+        };
+        if beat_length <= 0.0{
+            return time / rate;
+        }
+        let beats = (time - offset) / beat_length;
+        let snapped = SNAP_DIVISORS.iter()
+            .map(|divisor| (beats * divisor).round() / divisor)
+            .find(|snapped| ((snapped - beats) * beat_length).abs() <= 1.0)
+            .unwrap_or(beats);
+        (offset + snapped * beat_length) / rate
+    }
+}
+
+/// Moves a hit object along with its slider control points by the given offset.
+fn translate(h: &mut HitObject, dx: i32, dy: i32){
+    h.pos.x += dx;
+    h.pos.y += dy;
+    if let HitObjectKind::Slider(slider) = &mut h.kind{
+        for point in &mut slider.control_points{
+            point.x += dx;
+            point.y += dy;
+        }
+    }
+}
+
+/// Moves a hit object so that its position and all of its control points are inside the playfield.
+/// Sliders that are larger than the playfield are aligned to its top left corner.
+fn clamp_object(h: &mut HitObject){
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (h.pos.x, h.pos.y, h.pos.x, h.pos.y);
+    if let HitObjectKind::Slider(slider) = &h.kind{
+        for point in &slider.control_points{
+            min_x = min_x.min(point.x);
+            min_y = min_y.min(point.y);
+            max_x = max_x.max(point.x);
+            max_y = max_y.max(point.y);
+        }
+    }
+    let dx = if min_x < 0 { -min_x } else if max_x > PLAYFIELD_WIDTH { (PLAYFIELD_WIDTH - max_x).max(-min_x) } else { 0 };
+    let dy = if min_y < 0 { -min_y } else if max_y > PLAYFIELD_HEIGHT { (PLAYFIELD_HEIGHT - max_y).max(-min_y) } else { 0 };
+    if dx != 0 || dy != 0{
+        translate(h, dx, dy);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn assert_near(actual: (f64, f64), expected: (f64, f64)){
+        assert!((actual.0 - expected.0).abs() < 0.5 && (actual.1 - expected.1).abs() < 0.5, "{:?} is not {:?}", actual, expected);
+    }
+
+    /// A 4 key osu!mania map at 120 bpm with the given hit object lines.
+    fn mania_map(hit_objects: &[&str]) -> Beatmap{
+        let source = format!("osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nMode: 3\n\n[Difficulty]\nCircleSize:4\n\n[TimingPoints]\n0,500,4,2,0,100,1,0\n\n[HitObjects]\n{}\n", hit_objects.join("\n"));
+        Beatmap::parse(source.as_bytes()).unwrap()
+    }
+
+    /// Start time and end time of long notes of each hit object.
+    fn notes(map_data: &Beatmap) -> Vec<(i32, Option<i32>)>{
+        map_data.hit_objects.iter().map(|h| (h.start_time.0, match &h.kind{
+            HitObjectKind::Hold(k) => Some(k.end_time.0),
+            _ => None
+        })).collect()
+    }
+
+    #[test]
+    fn inverted_notes_run_to_the_next_note(){
+        let mut map_data = mania_map(&["64,192,1000,1,0,0:0:0:0:", "64,192,2000,128,0,2500:0:0:0:0:"]);
+        invert_long_notes(&mut map_data, 4, 4);
+        assert_eq!(notes(&map_data), vec![(1000, Some(1875)), (2000, Some(2375)), (2500, None)]);
+    }
+
+    #[test]
+    fn last_inverted_note_of_each_column_is_kept(){
+        let mut map_data = mania_map(&["64,192,1000,1,0,0:0:0:0:", "192,192,1500,1,0,0:0:0:0:", "192,192,2500,1,0,0:0:0:0:"]);
+        invert_long_notes(&mut map_data, 4, 4);
+        assert_eq!(notes(&map_data), vec![(1000, None), (1500, Some(2375)), (2500, None)]);
+    }
+
+    #[test]
+    fn linear_paths_are_cut_short_and_extended(){
+        let path = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)];
+        assert_near(point_at_length(&path, 150.0), (100.0, 50.0));
+        assert_near(point_at_length(&path, 250.0), (100.0, 150.0));
+    }
+
+    #[test]
+    fn perfect_circle_paths_follow_the_arc(){
+        let path = circular_arc_path(&[(0.0, 0.0), (50.0, 50.0), (100.0, 0.0)]).unwrap();
+        let half_circle = 50.0 * std::f64::consts::PI;
+        assert_near(point_at_length(&path, half_circle / 2.0), (50.0, 50.0));
+        assert_near(point_at_length(&path, half_circle), (100.0, 0.0));
+        // The arc bends the other way if the middle point is on the other side
+        let path = circular_arc_path(&[(0.0, 0.0), (50.0, -50.0), (100.0, 0.0)]).unwrap();
+        assert_near(point_at_length(&path, half_circle / 2.0), (50.0, -50.0));
+    }
+
+    #[test]
+    fn perfect_circle_paths_on_a_line_are_not_arcs(){
+        assert_eq!(circular_arc_path(&[(0.0, 0.0), (50.0, 0.0), (100.0, 0.0)]), None);
+    }
+
+    #[test]
+    fn bezier_paths_start_new_curves_at_repeated_points(){
+        let path = bezier_path(&[(0.0, 0.0), (100.0, 0.0), (100.0, 0.0), (100.0, 100.0)]);
+        assert_near(point_at_length(&path, 150.0), (100.0, 50.0));
+        let path = bezier_path(&[(0.0, 0.0), (50.0, 100.0), (100.0, 0.0)]);
+        assert_near(bezier_point(&[(0.0, 0.0), (50.0, 100.0), (100.0, 0.0)], 0.5), (50.0, 50.0));
+        assert_near(*path.last().unwrap(), (100.0, 0.0));
+    }
+
+    #[test]
+    fn catmull_paths_pass_through_their_points(){
+        let points = [(0.0, 0.0), (100.0, 0.0), (200.0, 100.0)];
+        let path = catmull_path(&points);
+        assert!(points.iter().all(|point| path.contains(point)));
+    }
+}
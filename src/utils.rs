@@ -1,136 +1,513 @@
-use std::{path::{PathBuf, Path}, fs::{File, OpenOptions}, io::{Write, ErrorKind, BufWriter}, sync::Arc, process, collections::HashSet};
+use std::{path::PathBuf, fs::{File, OpenOptions}, io::{Write, ErrorKind, BufWriter}, sync::Arc, process, collections::HashSet, ops::Range};
 use anyhow::{Result, anyhow};
-use libosu::prelude::*;
+use libosu::{prelude::*, events::Event};
 use std::process::Child;
 use tokio_tungstenite::connect_async;
 use tokio::{io::AsyncWriteExt, sync::Mutex};
 use futures_util::StreamExt;
 use serde_json::from_str;
-use crate::{structs::{MapOptions, Settings}, audio::*};
+use crate::{structs::{BpmInfo, GeneratedMap, LongNotes, MapOptions, MapOverrides, Mirror, OutputPaths, Settings, Severity}, audio::DecodedAudio, background, transforms};
 
 
 /// Generates an audio and .osu file using the given Settings and MapOptions structs.
-pub async fn generate_map(map: &MapOptions, settings: &Settings) -> Result<()>{
+pub async fn generate_map(map: &MapOptions, settings: &Settings) -> Result<GeneratedMap>{
+    // Check the options before anything is written
+    check_errors(map, settings)?;
     let path = &settings.songs_path.join(&map.map_path);
-    let rate = map.rate;
-    let map_file = File::open(path)?;
-    let mut map_data = libosu::beatmap::Beatmap::parse(map_file)?;
-    let audio_path = path.parent().unwrap().join(&map_data.audio_filename);
-    let cache_dir = dirs::cache_dir().ok_or(anyhow!("Couldn't find cache directory"))?.join("ruso");
-    if !cache_dir.exists(){
-        std::fs::create_dir_all(&cache_dir)?;
+    let rate = map.effective_rate();
+    let loaded = map.loaded_map(settings)?;
+    let audio_path = path.parent().unwrap().join(&loaded.beatmap.audio_filename);
+    let paths = output_paths(map, settings, &loaded.beatmap.audio_filename);
+
+    let mut audio_thread = None;
+    if rate != 1.0 && (settings.force_generation || !paths.audio.exists()){
+        // Generate audio file on a new thread
+        audio_thread = Some(tokio::task::spawn({
+            let new_audio_path = paths.audio.clone();
+            let change_pitch = settings.change_pitch;
+            async move{
+                DecodedAudio::decode(&audio_path)?.encode(&new_audio_path, rate, change_pitch)
+            }
+        }));
     }
 
-    // Open the cache file to append new maps or create a new one with help info
-    let mut cache_file = match OpenOptions::new().append(true).open(cache_dir.join("maps.txt")){
-        Ok(k) => k,
-        Err(e) if e.kind() == ErrorKind::NotFound => {
-            let mut temp = OpenOptions::new().create(true).append(true).open(cache_dir.join("maps.txt"))?;
-            writeln!(temp, "// Files generated by ruso")?;
-            writeln!(temp, "// Do not delete this file as it is used to keep track of files generated by ruso for easy removal if needed")?;
-            writeln!(temp, "// For safety reasons, ruso only removes files that start with your current osu! songs path")?;
-            temp
-        },
-        Err(e) => return Err(anyhow!("Error opening maps.txt: {}", e))
+    let generated = GeneratedMap{ path: paths.map.clone(), contents: map_contents(map, settings, &paths)? };
+
+    // Wait for the audio threat to finish and return an error if something went wrong
+    if let Some(audio_thread) = audio_thread{
+        audio_thread.await.map_err(|e| anyhow::anyhow!("Error generating audio file: {:?}", e))??;
+    } 
+
+    // Generate .osz file or .osu depending on user selection
+    if settings.generate_osz{
+        generate_osz(std::slice::from_ref(&generated), paths.background.as_slice())?;
+    }else{
+        File::create(&generated.path)?.write_all(generated.contents.as_bytes())?;
+    }
+    record_generated_files(std::slice::from_ref(&paths))?;
+
+    Ok(generated)
+}
+
+/// Generates every step of a training ladder into one mapset.
+/// The audio is only decoded once and encoded once for each rate, and all steps are written into the same .osz file.
+pub async fn generate_ladder(steps: &[MapOptions], settings: &Settings) -> Result<Vec<GeneratedMap>>{
+    let Some(first) = steps.first() else {
+        return Err(anyhow!("The ladder has no steps."));
     };
+    // Check the options of every step before anything is written
+    for step in steps{
+        check_errors(step, settings).map_err(|e| anyhow!("{}: {}", step.ladder_name().unwrap_or_default(), e))?;
+    }
+    let path = &settings.songs_path.join(&first.map_path);
+    let loaded = first.loaded_map(settings)?;
+    let audio_path = path.parent().unwrap().join(&loaded.beatmap.audio_filename);
+    let paths = steps.iter().map(|x| output_paths(x, settings, &loaded.beatmap.audio_filename)).collect::<Vec<OutputPaths>>();
+
+    // Steps with the same rate share their audio file
+    let mut audio_files: Vec<(f64, PathBuf)> = Vec::new();
+    for (step, step_paths) in steps.iter().zip(&paths){
+        let rate = step.effective_rate();
+        if rate != 1.0 && (settings.force_generation || !step_paths.audio.exists()) && !audio_files.iter().any(|(x, _)| *x == rate){
+            audio_files.push((rate, step_paths.audio.clone()));
+        }
+    }
+    let mut audio_thread = None;
+    if !audio_files.is_empty(){
+        audio_thread = Some(tokio::task::spawn_blocking({
+            let change_pitch = settings.change_pitch;
+            move || -> Result<()>{
+                let audio = DecodedAudio::decode(&audio_path)?;
+                for (rate, new_audio_path) in audio_files{
+                    audio.encode(&new_audio_path, rate, change_pitch)?;
+                }
+                Ok(())
+            }
+        }));
+    }
+
+    let generated = steps.iter().zip(&paths)
+        .map(|(step, step_paths)| Ok(GeneratedMap{ path: step_paths.map.clone(), contents: map_contents(step, settings, step_paths)? }))
+        .collect::<Result<Vec<GeneratedMap>>>()?;
+
+    if let Some(audio_thread) = audio_thread{
+        audio_thread.await.map_err(|e| anyhow::anyhow!("Error generating audio file: {:?}", e))??;
+    }
+
+    if settings.generate_osz{
+        let backgrounds = paths.iter().filter_map(|x| x.background.clone()).collect::<Vec<PathBuf>>();
+        generate_osz(&generated, &backgrounds)?;
+    }else{
+        for map in &generated{
+            File::create(&map.path)?.write_all(map.contents.as_bytes())?;
+        }
+    }
+    record_generated_files(&paths)?;
+
+    Ok(generated)
+}
+
+/// Returns the validation errors of the options as one error, including the content of the map that would be lost.
+fn check_errors(map: &MapOptions, settings: &Settings) -> Result<()>{
+    let errors = map.validate(settings).into_iter()
+        .filter(|x| x.severity == Severity::Error)
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+    if !errors.is_empty(){
+        return Err(anyhow!("{}", errors.join("\n")));
+    }
+    Ok(())
+}
+
+/// Creates the contents of the .osu file of the generated map and writes its processed background.
+fn map_contents(map: &MapOptions, settings: &Settings, paths: &OutputPaths) -> Result<String>{
+    let path = &settings.songs_path.join(&map.map_path);
+    let rate = map.effective_rate();
+    let loaded = map.loaded_map(settings)?;
+    let source = &loaded.source;
+    let mut map_data = loaded.beatmap.clone();
+
+    // Converted mania maps get their key count from the conversion instead of the circle size
+    let converting_to_mania = map.target_mode() == Mode::Mania && map_data.mode != Mode::Mania;
+    transforms::convert_mode(&mut map_data, map.target_mode(), &loaded.difficulty)?;
+    let original_cs = map_data.difficulty.circle_size;
+    let circle_size = if converting_to_mania { original_cs } else { map.circle_size };
     map_data.difficulty.approach_rate = map.approach_rate;
-    map_data.difficulty.circle_size = map.circle_size;
+    map_data.difficulty.circle_size = circle_size;
     map_data.difficulty.hp_drain_rate = map.hp_drain;
     map_data.difficulty.overall_difficulty = map.overall_difficulty;
-    map_data.preview_time.0 = (*map_data.preview_time as f64 / rate).round() as i32;
+    // Sliders keep their duration when the slider velocity changes, so their length is scaled with it.
+    // The control points stay the same, so osu! cuts shorter sliders short along their path and extends longer ones past their end
+    let length_factor = map.slider_multiplier / map_data.difficulty.slider_multiplier;
+    if length_factor != 1.0{
+        for h in map_data.hit_objects.iter_mut(){
+            if let HitObjectKind::Slider(slider) = &mut h.kind{
+                slider.pixel_length *= length_factor;
+            }
+        }
+    }
+    map_data.difficulty.slider_multiplier = map.slider_multiplier;
+    map_data.difficulty.slider_tick_rate = map.slider_tick_rate;
+    map_data.stack_leniency = map.stack_leniency;
+    // Preview the practice range instead of the usual part of the song if there is one
+    let preview_time = map.practice_range.map_or(*map_data.preview_time, |(start, _)| start);
+    map_data.preview_time.0 = (preview_time as f64 / rate).round() as i32;
+
+    // Replace the metadata with the overrides of the user before tagging the map
+    let overrides = &settings.overrides;
+    if let Some(creator) = &overrides.creator{
+        map_data.creator = creator.clone();
+    }
+    if let Some(source) = &overrides.source{
+        map_data.source = source.clone();
+    }
+    if let Some(title_unicode) = &overrides.title_unicode{
+        map_data.title_unicode = title_unicode.clone();
+    }
+    if let Some(artist_unicode) = &overrides.artist_unicode{
+        map_data.artist_unicode = artist_unicode.clone();
+    }
+    if let Some(tags) = &overrides.tags{
+        map_data.tags = tags.split_whitespace().map(String::from).collect();
+    }
     map_data.tags.push("ruso-map".to_string());
 
+    // Bake the position changes of the selected mods and transforms into the map
+    if map_data.mode == Mode::Osu{
+        if map.mods.hard_rock{
+            transforms::flip_vertical(&mut map_data);
+        }
+        transforms::mirror(&mut map_data, map.mirror);
+        if map.rotation % 360.0 != 0.0{
+            transforms::rotate(&mut map_data, map.rotation);
+        }
+        if map.spacing != 1.0{
+            transforms::scale_spacing(&mut map_data, map.spacing);
+        }
+    }else if map_data.mode == Mode::Mania{
+        let keys = circle_size as u32;
+        transforms::convert_key_count(&mut map_data, original_cs.round() as u32, keys);
+        if map.mirror == Mirror::Horizontal{
+            transforms::mirror_columns(&mut map_data, keys);
+        }
+        if let Some(seed) = map.random_seed{
+            transforms::shuffle_columns(&mut map_data, keys, seed);
+        }
+        match map.long_notes{
+            LongNotes::Keep => (),
+            LongNotes::Full => transforms::full_long_notes(&mut map_data, keys, map.ln_gap),
+            LongNotes::Remove => transforms::remove_long_notes(&mut map_data),
+            LongNotes::Invert => transforms::invert_long_notes(&mut map_data, keys, map.ln_gap),
+        }
+    }
+
     // Change beatmap properties to match those given by the user
-    let mut new_audio_path = audio_path.clone();
     if rate != 1.0{
-        let new_name = format!("{}({}).{}", &audio_path.file_stem().unwrap().to_str().unwrap(), rate, audio_path.extension().unwrap().to_str().unwrap());
-        new_audio_path.set_file_name(&new_name);
-        map_data.audio_filename = new_name;
+        map_data.audio_filename = paths.audio.file_name().unwrap().to_string_lossy().to_string();
         map_data.difficulty_name += format!(" {}x ({}bpm)", rate, (map.bpm as f64 * rate) as usize).as_str(); 
     }else{
         map_data.difficulty_name += format!(" (AR {} CS {} HP {} OD {})", map.approach_rate, map.circle_size, map.hp_drain, map.overall_difficulty ).as_str();
     }
+    if let Some(variant_name) = map.variant_name(){
+        map_data.difficulty_name += format!(" {}", variant_name).as_str();
+    }
+    // The step comes first so the steps of a ladder sort in order
+    if let Some(ladder_name) = map.ladder_name(){
+        map_data.difficulty_name = format!("{} {}", ladder_name, map_data.difficulty_name);
+    }
 
-    let mut audio_thread = None;
-    if settings.force_generation || !new_audio_path.exists(){
-        // Generate audio file on a new thread
-        audio_thread = Some(tokio::task::spawn({
-            let audio_path = audio_path.clone();
-            let change_pitch = settings.change_pitch;
-            async move{
-                generate_audio(&audio_path, rate, change_pitch)
-            }
-        }));
+    if let Some((start, end)) = map.practice_range{
+        transforms::practice_range(&mut map_data, start, end);
     }
 
+    // Simplify the map before changing its timing since the transforms depend on the original timing points
+    if map_data.mode != Mode::Mania{
+        transforms::adjust_spinners(&mut map_data, map.spinners);
+        if let Some(divisor) = map.stream_thinning{
+            transforms::thin_streams(&mut map_data, divisor);
+        }
+        if map.sliders_to_circles{
+            transforms::sliders_to_circles(&mut map_data, map.slider_tail_circles);
+        }
+    }
+
+    // Store the seed in the tags so the random map can be recreated
+    if let (Some(seed), Mode::Osu | Mode::Mania) = (map.random_seed, map_data.mode){
+        map_data.tags.push(format!("ruso-seed-{}", seed));
+    }
+    let mut randomizer = map.random_seed.filter(|_| map_data.mode == Mode::Osu).map(transforms::Randomizer::new);
+
+    // libosu rounds timing point offsets to whole milliseconds, so the exact ones are read from the original map
+    let offsets = match timing_offsets(source){
+        offsets if offsets.len() == map_data.timing_points.len() => offsets,
+        _ => map_data.timing_points.iter().map(|x| x.time.0 as f64).collect()
+    };
+    let grid = transforms::BeatGrid::new(&map_data.timing_points, &offsets);
+
     // Change time value for each hit object to match the new rate of the map
+    // The times are scaled along the beat grid to keep them snapped at any rate
     for h in &mut map_data.hit_objects{
-        h.start_time.0 = (*h.start_time as f64 / rate).round() as i32;
+        if let Some(randomizer) = &mut randomizer{
+            randomizer.apply(h);
+        }
+        h.start_time.0 = grid.scale(h.start_time.0, rate).round() as i32;
         match &mut h.kind {
             HitObjectKind::Hold(k) => {
-                k.end_time.0 = (*k.end_time as f64 / rate).round() as i32;
+                k.end_time.0 = grid.scale(k.end_time.0, rate).round() as i32;
             },
             HitObjectKind::Spinner(k) => {
-                k.end_time.0 = (*k.end_time as f64 / rate).round() as i32;
+                k.end_time.0 = grid.scale(k.end_time.0, rate).round() as i32;
             },
             _ => {}
         }
     }
 
     // Change time value for each timing point to match the new rate of the map
-    for point in &mut map_data.timing_points{
-        point.time.0 = (point.time.0 as f64 / rate).round() as i32;
+    // The exact offsets are written to the map after it is serialized
+    let new_offsets = offsets.iter().map(|x| x / rate).collect::<Vec<f64>>();
+    for (point, offset) in map_data.timing_points.iter_mut().zip(&new_offsets){
+        point.time.0 = offset.round() as i32;
         if let TimingPointKind::Uninherited(point) = &mut point.kind{
             point.mpb /= rate;
         }
     }
 
-    // Generate path for the new .osu file
-    let new_path = PathBuf::from(format!("{}({}).osu", path.parent().unwrap().join(path.file_stem().unwrap()).display(), rate));
+    // Only the background and breaks are kept since everything else in the events is either a
+    // video or part of the storyboard
+    if map.strip_storyboard{
+        map_data.events.retain(|x| matches!(x, Event::Background(_) | Event::Break(_)));
+    }
 
-    // Wait for the audio threat to finish and return an error if something went wrong
-    if let Some(audio_thread) = audio_thread{
-        audio_thread.await.map_err(|e| anyhow::anyhow!("Error generating audio file: {:?}", e))??;
-    } 
-    
-    // Generate .osz file or .osu depending on user selection
-    if settings.generate_osz{
-        generate_osz(&new_path, &map_data)?;
-    }else{
-        write!(File::create(&new_path)?,"{}", map_data)?;
+    // Write the processed background next to the new map
+    if let Some(background_path) = &paths.background{
+        let source = map_data.events.iter().find_map(|x| match x{
+            Event::Background(b) => Some(path.parent().unwrap().join(&b.filename)),
+            _ => None
+        });
+        background::process_background(source.as_deref(), &map.background_options, background_path)?;
+    }
+
+    // The appearance overrides are applied to the serialized map since they are written the same
+    // way as the original .osu file
+    let mut lines = map_data.to_string().lines().map(String::from).collect::<Vec<String>>();
+    set_timing_offsets(&mut lines, &new_offsets);
+    apply_appearance_overrides(&mut lines, &settings.overrides);
+    if let Some(background_path) = &paths.background{
+        set_background_event(&mut lines, &background_path.file_name().unwrap().to_string_lossy());
     }
+    Ok(lines.join("\n"))
+}
 
-    // Write the new paths to the cache file for easy deletion and space usage calculation
-    writeln!(cache_file, "{}", new_path.display())?;
-    writeln!(cache_file, "{}", new_audio_path.display())?;
+/// Writes the paths of generated files to the cache file for easy deletion and space usage calculation.
+fn record_generated_files(paths: &[OutputPaths]) -> Result<()>{
+    let cache_dir = dirs::cache_dir().ok_or(anyhow!("Couldn't find cache directory"))?.join("ruso");
+    if !cache_dir.exists(){
+        std::fs::create_dir_all(&cache_dir)?;
+    }
 
+    // Open the cache file to append new maps or create a new one with help info
+    let mut cache_file = match OpenOptions::new().append(true).open(cache_dir.join("maps.txt")){
+        Ok(k) => k,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let mut temp = OpenOptions::new().create(true).append(true).open(cache_dir.join("maps.txt"))?;
+            writeln!(temp, "// Files generated by ruso")?;
+            writeln!(temp, "// Do not delete this file as it is used to keep track of files generated by ruso for easy removal if needed")?;
+            writeln!(temp, "// For safety reasons, ruso only removes files that start with your current osu! songs path")?;
+            temp
+        },
+        Err(e) => return Err(anyhow!("Error opening maps.txt: {}", e))
+    };
+    for paths in paths{
+        writeln!(cache_file, "{}", paths.map.display())?;
+        writeln!(cache_file, "{}", paths.audio.display())?;
+        if let Some(background_path) = &paths.background{
+            writeln!(cache_file, "{}", background_path.display())?;
+        }
+    }
     Ok(())
 }
 
-/// Generates a new audio file with the given rate.
-fn generate_audio(audio_path: &PathBuf, rate: f64, change_pitch: bool) -> Result<()>{
-    // Generate audio file based on extension
-    match audio_path.extension().unwrap_or(std::ffi::OsStr::new("")).to_str().unwrap(){
-        "ogg" => change_speed_ogg(audio_path, rate, change_pitch)?,
-        "wav" => change_speed_wav(audio_path, rate, change_pitch)?,
-        "mp3" => change_speed_mp3(audio_path, rate, change_pitch)?,
-        _ => {
-            // Attempt to process file as mp3 if it is not a known file type
-            if change_speed_mp3(audio_path, rate, change_pitch).is_err(){
-                return Err(anyhow!("Unsupported/unknown file type!"))
-            }
+/// Generates the paths of the files generate_map writes for the map.
+pub fn output_paths(map: &MapOptions, settings: &Settings, audio_filename: &str) -> OutputPaths{
+    let path = settings.songs_path.join(&map.map_path);
+    let rate = map.effective_rate();
+    let audio_path = path.parent().unwrap().join(audio_filename);
+    let audio = if rate != 1.0{
+        audio_path.with_file_name(format!("{}({}).{}", audio_path.file_stem().unwrap().to_str().unwrap(), rate, audio_path.extension().unwrap().to_str().unwrap()))
+    }else{
+        audio_path
+    };
+
+    // Include the ladder step, mods and transforms so that maps with the same rate don't overwrite each other
+    let name = [map.ladder_name(), Some(rate.to_string()), map.variant_name()].into_iter().flatten().collect::<Vec<String>>().join(" ");
+    let map_path = PathBuf::from(format!("{}({}).osu", path.parent().unwrap().join(path.file_stem().unwrap()).display(), name));
+    OutputPaths{
+        background: (!map.background_options.is_empty()).then(|| map_path.with_extension("jpg")),
+        osz: settings.generate_osz.then(|| path.parent().unwrap().with_extension("osz")),
+        map: map_path,
+        audio,
+    }
+}
+
+/// Reads the offsets of the timing points in a .osu file, which can have fractional milliseconds.
+fn timing_offsets(contents: &str) -> Vec<f64>{
+    let lines = contents.lines().map(String::from).collect::<Vec<String>>();
+    section_range(&lines, "TimingPoints").map(|range| lines[range].iter()
+        .filter(|x| !x.trim().is_empty() && !x.starts_with("//"))
+        .filter_map(|x| x.split(',').next()?.trim().parse::<f64>().ok())
+        .collect()
+    ).unwrap_or_default()
+}
+
+/// Replaces the offsets of the timing points in a serialized .osu file with the given ones.
+/// Nothing is replaced if the amount of offsets does not match the amount of timing points.
+fn set_timing_offsets(lines: &mut [String], offsets: &[f64]){
+    let Some(range) = section_range(lines, "TimingPoints") else { return };
+    let timing_lines = range.filter(|&x| !lines[x].trim().is_empty() && !lines[x].starts_with("//")).collect::<Vec<usize>>();
+    if timing_lines.len() != offsets.len(){
+        return;
+    }
+    for (ind, offset) in timing_lines.into_iter().zip(offsets){
+        if let Some((_, rest)) = lines[ind].split_once(','){
+            lines[ind] = format!("{},{}", round_dec(*offset, 3), rest);
+        }
+    }
+}
+
+/// Applies the [General] and [Colours] overrides to the lines of a serialized .osu file.
+fn apply_appearance_overrides(lines: &mut Vec<String>, overrides: &MapOverrides){
+    let flags = [
+        ("Countdown", overrides.countdown),
+        ("LetterboxInBreaks", overrides.letterbox_in_breaks),
+        ("WidescreenStoryboard", overrides.widescreen_storyboard),
+    ];
+    for (key, value) in flags{
+        if let Some(value) = value{
+            set_osu_value(lines, "General", key, if value { "1" } else { "0" });
+        }
+    }
+    if !overrides.combo_colours.is_empty(){
+        remove_osu_values(lines, "Colours", |key| key.starts_with("Combo"));
+        for (ind, colour) in overrides.combo_colours.iter().enumerate(){
+            set_osu_value(lines, "Colours", &format!("Combo{}", ind + 1), &colour.to_osu());
         }
+    }
+    if let Some(colour) = overrides.slider_border{
+        set_osu_value(lines, "Colours", "SliderBorder", &colour.to_osu());
+    }
+    if let Some(colour) = overrides.slider_track{
+        set_osu_value(lines, "Colours", "SliderTrackOverride", &colour.to_osu());
+    }
+}
+
+/// Points the background event of a serialized .osu file to the given file name,
+/// adding a background event if the map does not have one.
+fn set_background_event(lines: &mut Vec<String>, file_name: &str){
+    let event = format!("0,0,\"{}\",0,0", file_name);
+    let Some(range) = section_range(lines, "Events") else {
+        let ind = lines.iter().position(|x| x.trim() == "[TimingPoints]").unwrap_or(lines.len());
+        lines.splice(ind..ind, ["[Events]".to_string(), event, String::new()]);
+        return;
     };
+    match range.clone().find(|&x| lines[x].starts_with("0,")){
+        // Keep the offset of the original background
+        Some(ind) => {
+            let offset = lines[ind].splitn(4, ',').nth(3).map(|x| x.to_string());
+            lines[ind] = match offset{
+                Some(offset) => format!("0,0,\"{}\",{}", file_name, offset),
+                None => event
+            };
+        },
+        None => lines.insert(range.start, event)
+    }
+}
 
-    Ok(())
+/// Returns the range of lines of a section in a serialized .osu file, excluding its header.
+fn section_range(lines: &[String], section: &str) -> Option<Range<usize>>{
+    let header = format!("[{}]", section);
+    let start = lines.iter().position(|x| x.trim() == header)? + 1;
+    let end = lines[start..].iter().position(|x| x.starts_with('[')).map_or(lines.len(), |x| x + start);
+    Some(start..end)
+}
+
+/// Sets the value of a key in a section of a serialized .osu file.
+/// The key is added to the end of the section if it does not exist, and the section
+/// is added before the hit objects if it does not exist either.
+fn set_osu_value(lines: &mut Vec<String>, section: &str, key: &str, value: &str){
+    let line = format!("{}: {}", key, value);
+    let Some(range) = section_range(lines, section) else {
+        let ind = lines.iter().position(|x| x.trim() == "[HitObjects]").unwrap_or(lines.len());
+        lines.splice(ind..ind, [format!("[{}]", section), line, String::new()]);
+        return;
+    };
+    match range.clone().find(|&x| lines[x].split(':').next().map(str::trim) == Some(key)){
+        Some(ind) => lines[ind] = line,
+        None => {
+            // Insert after the last non-empty line to keep the blank line between sections
+            let ind = range.clone().rev().find(|&x| !lines[x].trim().is_empty()).map_or(range.start, |x| x + 1);
+            lines.insert(ind, line);
+        }
+    }
 }
 
-/// Calculates the bpm of beatmap using the timing points.
-pub fn calculate_bpm(points: &[TimingPoint]) -> usize{
-    (60000.0 / points.iter().filter_map(|x| match &x.kind{
-        TimingPointKind::Uninherited(k) => Some(k.mpb.abs()),
-        _ => None
-    }).min_by(|x,y| x.partial_cmp(y).unwrap()).unwrap_or(100.0)).round() as usize
+/// Removes every key of a section in a serialized .osu file that matches the predicate.
+fn remove_osu_values(lines: &mut Vec<String>, section: &str, predicate: impl Fn(&str) -> bool){
+    if let Some(range) = section_range(lines, section){
+        let mut ind = range.start;
+        let mut end = range.end;
+        while ind < end{
+            match lines[ind].split_once(':'){
+                Some((key, _)) if predicate(key.trim()) => {
+                    lines.remove(ind);
+                    end -= 1;
+                },
+                _ => ind += 1
+            }
+        }
+    }
+}
+
+/// Analyzes the bpm of a beatmap using its timing points.
+/// Each bpm is weighted by how long it lasts, with the last one lasting until `end_time`.
+pub fn analyze_bpm(points: &[TimingPoint], end_time: i32) -> BpmInfo{
+    // Merge consecutive timing points with the same bpm into one section
+    let mut sections: Vec<(i32, f64)> = Vec::new();
+    for point in points{
+        if let TimingPointKind::Uninherited(k) = &point.kind{
+            if k.mpb <= 0.0{
+                continue;
+            }
+            let bpm = round_dec(60000.0 / k.mpb, 2);
+            if sections.last().map(|x| x.1) != Some(bpm){
+                sections.push((point.time.0, bpm));
+            }
+        }
+    }
+    if sections.is_empty(){
+        return BpmInfo::default();
+    }
+
+    // Sections that start after the last hit object are ignored unless there is nothing else
+    let durations = sections.iter().enumerate().map(|(ind, (start, bpm))|{
+        let end = sections.get(ind + 1).map_or(end_time, |x| x.0);
+        (*bpm, (end - start).max(0))
+    }).collect::<Vec<(f64, i32)>>();
+    let played = durations.iter().filter(|x| x.1 > 0).map(|x| x.0).collect::<Vec<f64>>();
+    let played = if played.is_empty() { durations.iter().map(|x| x.0).collect() } else { played };
+
+    let mut total_durations: Vec<(f64, i32)> = Vec::new();
+    for (bpm, duration) in durations{
+        match total_durations.iter_mut().find(|x| x.0 == bpm){
+            Some(total) => total.1 += duration,
+            None => total_durations.push((bpm, duration))
+        }
+    }
+    BpmInfo{
+        dominant: total_durations.iter().max_by_key(|x| x.1).map_or(sections[0].1, |x| x.0),
+        min: played.iter().copied().fold(f64::MAX, f64::min),
+        max: played.iter().copied().fold(f64::MIN, f64::max),
+        sections,
+    }
 }
 
 /// Removes all files generated by ruso.
@@ -215,12 +592,23 @@ pub fn read_space(file_name: &str) -> Result<usize>{
     })
 }
 
+/// Generates a new seed for the randomizer from the current time.
+pub fn new_seed() -> u64{
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|x| x.as_nanos() as u64).unwrap_or(0) % 1_000_000
+}
+
 /// Rounds a float to the given number of decimal places.
 pub fn round_dec(x: f64, decimals: u32) -> f64 {
     let y = 10i32.pow(decimals) as f64;
     (x * y).round() / y
 }
 
+/// Formats a time in milliseconds as minutes and seconds, e.g. 1:05.
+pub fn format_time(ms: i32) -> String{
+    let seconds = ms.max(0) / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
 /// Connects to gosumemory with the settings from the Settings struct.
 pub async fn gosu_websocket_listen(settings: &Settings) -> Result<()>{
     let (socket, response) = connect_async(&settings.websocket_url).await?;
@@ -317,14 +705,24 @@ pub fn write_config(settings: &Settings, file_name: &str) -> Result<()>{
     Ok(())
 }
 
-/// Generates an .osz file from an .osu file.
-pub fn generate_osz(map_path: &Path, map_data: &Beatmap) -> Result<()>{
+/// Generates an .osz file from generated maps of the same mapset.
+/// Files other than the maps, such as their processed backgrounds, are added next to them.
+pub fn generate_osz(maps: &[GeneratedMap], extra_files: &[PathBuf]) -> Result<()>{
+    let map_path = &maps.first().ok_or(anyhow!("No maps to add to the .osz file."))?.path;
     let osz_file = File::create(map_path.parent().ok_or(anyhow!("Couldn't get parent path."))?.with_extension("osz"))?;
     let mut zip = zip::ZipWriter::new(BufWriter::new(osz_file));
-    zip.start_file(map_path.file_name()
-        .ok_or(anyhow!("Couldn't get file name."))?.to_str()
-        .ok_or(anyhow!("Couldn't convert file name to a UTF-8 string."))?, Default::default())?;
-    map_data.write(&mut zip)?;
+    for map in maps{
+        zip.start_file(map.path.file_name()
+            .ok_or(anyhow!("Couldn't get file name."))?.to_str()
+            .ok_or(anyhow!("Couldn't convert file name to a UTF-8 string."))?, Default::default())?;
+        zip.write_all(map.contents.as_bytes())?;
+    }
+    for file in extra_files{
+        zip.start_file(file.file_name()
+            .ok_or(anyhow!("Couldn't get file name."))?.to_str()
+            .ok_or(anyhow!("Couldn't convert file name to a UTF-8 string."))?, Default::default())?;
+        zip.write_all(&std::fs::read(file)?)?;
+    }
 
     zip.finish()?;
 
@@ -348,3 +746,45 @@ pub fn generate_example_theme(file_name: &str) -> Result<()>{
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    /// Timing points parsed from lines of the [TimingPoints] section of a map.
+    fn timing_points(lines: &[&str]) -> Vec<TimingPoint>{
+        let source = format!("osu file format v14\n\n[General]\nAudioFilename: audio.mp3\nMode: 0\n\n[TimingPoints]\n{}\n\n[HitObjects]\n", lines.join("\n"));
+        Beatmap::parse(source.as_bytes()).unwrap().timing_points
+    }
+
+    #[test]
+    fn dominant_bpm_is_the_longest_one(){
+        let points = timing_points(&["0,500,4,2,0,100,1,0", "10000,400,4,2,0,100,1,0"]);
+        let info = analyze_bpm(&points, 15000);
+        assert_eq!(info, BpmInfo{ dominant: 120.0, min: 120.0, max: 150.0, sections: vec![(0, 120.0), (10000, 150.0)] });
+        // Durations of the same bpm are added up
+        let points = timing_points(&["0,500,4,2,0,100,1,0", "4000,400,4,2,0,100,1,0", "10000,500,4,2,0,100,1,0"]);
+        assert_eq!(analyze_bpm(&points, 16000).dominant, 120.0);
+    }
+
+    #[test]
+    fn sections_of_the_same_bpm_are_merged(){
+        let points = timing_points(&["0,500,4,2,0,100,1,0", "2000,-50,4,2,0,100,0,0", "4000,500,4,2,0,100,1,0"]);
+        assert_eq!(analyze_bpm(&points, 8000).sections, vec![(0, 120.0)]);
+    }
+
+    #[test]
+    fn sections_after_the_last_object_are_ignored(){
+        let points = timing_points(&["0,500,4,2,0,100,1,0", "20000,300,4,2,0,100,1,0"]);
+        let info = analyze_bpm(&points, 10000);
+        assert_eq!((info.dominant, info.min, info.max), (120.0, 120.0, 120.0));
+        assert_eq!(info.sections.len(), 2);
+    }
+
+    #[test]
+    fn maps_without_bpm_use_the_default(){
+        assert_eq!(analyze_bpm(&[], 1000), BpmInfo::default());
+        let points = timing_points(&["0,-100,4,2,0,100,0,0"]);
+        assert_eq!(analyze_bpm(&points, 1000), BpmInfo::default());
+    }
+}